@@ -0,0 +1,121 @@
+use crate::daemon::Remote;
+use crate::{AppState, Result};
+use ratatui::style::Color;
+use serde_json::json;
+use std::{
+    io::{self, Write},
+    time::{Duration, SystemTime},
+};
+
+const BAR_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub(crate) enum BarFormat {
+    /// JSON object per line for waybar's custom module (`return-type: json`)
+    Waybar,
+    /// i3bar protocol stream (header, then an endless array of block lists)
+    I3bar,
+    /// JSON block per line for i3blocks (`format=json`, `interval=persist`)
+    I3blocks,
+    /// tmux status-line text with #[fg=...] colours
+    Tmux,
+}
+
+/// Streams the timer state in a status-bar format, printing a new line only
+/// when the rendered output changes. Follows the daemon when one is running;
+/// otherwise shows the saved timer state as stopped, without counting down.
+pub(crate) fn run(mut app: AppState, format: BarFormat, once: bool) -> Result<()> {
+    let mut out = io::stdout().lock();
+    if let BarFormat::I3bar = format
+        && !once
+    {
+        writeln!(out, "{{\"version\":1}}\n[")?;
+    }
+
+    let mut last = String::new();
+    loop {
+        if app.remote.is_none() {
+            app.remote = Remote::connect();
+        }
+        app.sync_remote();
+        if !app.remote.as_ref().is_some_and(|r| r.connected()) {
+            // Nothing is running the timer, so don't let it tick.
+            app.clock.pause(SystemTime::now());
+        }
+
+        let line = render(&app, format);
+        if line != last {
+            let sep = if matches!(format, BarFormat::I3bar) && !once { "," } else { "" };
+            if writeln!(out, "{}{}", line, sep).and_then(|_| out.flush()).is_err() {
+                // The bar went away.
+                return Ok(());
+            }
+            last = line;
+        }
+
+        if once {
+            return Ok(());
+        }
+        std::thread::sleep(BAR_INTERVAL);
+    }
+}
+
+fn render(app: &AppState, format: BarFormat) -> String {
+    let secs = app.time_remaining().as_secs();
+    let clock = format!("{:02}:{:02}", secs / 60, secs % 60);
+    let name = app.phase.name();
+    let icon = name.split_whitespace().next().unwrap_or("🍅");
+    let running = app.remote.as_ref().is_some_and(|r| r.connected());
+    let state = if !running {
+        "stopped"
+    } else if app.paused() {
+        "paused"
+    } else {
+        "running"
+    };
+    let text = if state == "running" {
+        format!("{} {}", icon, clock)
+    } else {
+        format!("{} {} ⏸", icon, clock)
+    };
+    let percent = (app.progress_ratio() * 100.0).round() as u8;
+    let color = hex(app.phase_color());
+    let tooltip = format!(
+        "{}\n{} left • {}% • session {} of {}",
+        name,
+        clock,
+        percent,
+        ((app.session_count.max(1) - 1) % app.sessions_before_long_break.max(1)) + 1,
+        app.sessions_before_long_break,
+    );
+
+    match format {
+        BarFormat::Waybar => json!({
+            "text": text,
+            "alt": app.phase.to_str(),
+            "tooltip": tooltip,
+            "class": [app.phase.to_str(), state],
+            "percentage": percent,
+        }).to_string(),
+        BarFormat::I3bar => json!([{
+            "name": "rtimer",
+            "instance": app.phase.to_str(),
+            "full_text": text,
+            "short_text": clock,
+            "color": color,
+        }]).to_string(),
+        BarFormat::I3blocks => json!({
+            "full_text": text,
+            "short_text": clock,
+            "color": color,
+        }).to_string(),
+        BarFormat::Tmux => format!("#[fg={}]{}#[default]", color, text),
+    }
+}
+
+fn hex(color: Color) -> String {
+    match color {
+        Color::Rgb(r, g, b) => format!("#{:02x}{:02x}{:02x}", r, g, b),
+        _ => "#ffffff".into(),
+    }
+}
@@ -0,0 +1,208 @@
+use crate::daemon::{Client, Request, Response, Status};
+use crate::storage::{self, get_path, load_json, save_json, JsonStorage, Storage};
+use crate::{AppState, Config, Result, Statistics, StorageBackend, Totals};
+use serde::Serialize;
+use std::path::Path;
+
+/// Status as printed by `rtimer status --json`.
+#[derive(Serialize)]
+struct CliStatus<'a> {
+    running: bool,
+    #[serde(flatten)]
+    status: &'a Status,
+}
+
+/// Totals as printed by `rtimer stats --json`.
+#[derive(Serialize)]
+struct CliStats {
+    today: Totals,
+    total: Totals,
+    archived: Totals,
+    sessions_recorded: usize,
+}
+
+/// Sends a one-shot subcommand's request to the daemon if one is
+/// listening, otherwise applies it to the saved timer state on disk.
+pub(crate) fn run(req: Request, json: bool, mut app: AppState) -> Result<()> {
+    let (resp, running) = match Client::connect() {
+        Ok(mut client) => (client.request(&req)?, true),
+        Err(_) => {
+            let mutates = !matches!(req, Request::Status);
+            let _lock = match mutates {
+                true => Some(storage::lock_data_dir()?.ok_or_else(storage::busy_message)?),
+                false => None,
+            };
+            let resp = app.handle_request(req);
+            if mutates && resp.ok {
+                app.save_state(true);
+            }
+            (resp, false)
+        }
+    };
+
+    print_response(&resp, running, json)
+}
+
+/// Prints today's and all-time totals. With `rebuild`, leaves out the
+/// totals migrated from older versions so that everything comes from the
+/// recorded sessions alone; `force` also drops them from storage, after
+/// backing them up.
+pub(crate) fn stats(app: AppState, rebuild: bool, force: bool, json: bool) -> Result<()> {
+    let mut stats = if force {
+        if Client::connect().is_ok() {
+            return Err("a daemon is running; stop it before rebuilding statistics".into());
+        }
+        app.stats
+    } else {
+        latest_stats(app.stats)?
+    };
+
+    if rebuild {
+        let before = stats.totals();
+        let archived = std::mem::take(&mut stats.archived);
+        let after = stats.totals();
+        if force && archived != Totals::default() {
+            let _lock = storage::lock_data_dir()?.ok_or_else(storage::busy_message)?;
+            let backup = get_path("archived_totals.json.bak");
+            save_json(&backup, &archived)?;
+            if let Some(mut store) = app.store {
+                store.save(&stats)?;
+            }
+            eprintln!("Dropped the carried-over totals; they were saved to {}", backup.display());
+        }
+        if !json {
+            println!(
+                "From {} recorded sessions: {} → {} sessions, {} → {} focus, {} → {} break",
+                stats.session_history.len(),
+                before.work_sessions, after.work_sessions,
+                hours(before.work_secs), hours(after.work_secs),
+                hours(before.break_secs), hours(after.break_secs),
+            );
+            if !force && archived != Totals::default() {
+                println!("Nothing was changed; add --force to drop the carried-over totals for good.");
+            }
+            return Ok(());
+        }
+    }
+
+    let today = stats.day_totals(chrono::Local::now().date_naive());
+    let total = stats.totals();
+    if json {
+        println!("{}", serde_json::to_string(&CliStats {
+            today,
+            total,
+            archived: stats.archived,
+            sessions_recorded: stats.session_history.len(),
+        })?);
+        return Ok(());
+    }
+
+    println!("Today     {} sessions  •  {} focus", today.work_sessions, hours(today.work_secs));
+    println!(
+        "All time  {} sessions  •  {} focus  •  {} break",
+        total.work_sessions, hours(total.work_secs), hours(total.break_secs),
+    );
+    if stats.archived != Totals::default() {
+        println!(
+            "          ({} sessions and {} focus carried over from before the full history was kept)",
+            stats.archived.work_sessions, hours(stats.archived.work_secs),
+        );
+    }
+    Ok(())
+}
+
+/// The running daemon's statistics, which may include sessions it has not
+/// saved yet, or `stats` as loaded from disk when no daemon is running.
+pub(crate) fn latest_stats(stats: Statistics) -> Result<Statistics> {
+    match Client::connect() {
+        Ok(mut client) => Ok(client.request(&Request::Snapshot)?.snapshot.map_or(stats, |s| s.stats)),
+        Err(_) => Ok(stats),
+    }
+}
+
+fn hours(secs: u64) -> String {
+    format!("{:.1}h", secs as f64 / 3600.0)
+}
+
+/// Copies statistics and notes into `target` and makes it the backend used
+/// from now on. Sessions already present in the target are kept; the
+/// source's notes replace the target's.
+pub(crate) fn switch_storage(app: AppState, target: StorageBackend, from: Option<&Path>) -> Result<()> {
+    if Client::connect().is_ok() {
+        return Err("a daemon is running; stop it (`rtimer daemon` owns the data) before switching storage".into());
+    }
+    let _lock = storage::lock_data_dir()?.ok_or_else(storage::busy_message)?;
+
+    let stats = match from {
+        Some(path) => JsonStorage { path: path.to_path_buf() }.load()?,
+        None if target == app.storage_backend => {
+            println!("Already using {} storage", target.label());
+            return Ok(());
+        }
+        None => app.stats,
+    };
+
+    if target == StorageBackend::Json {
+        let existing = get_path(target.file_name());
+        if existing.exists() {
+            std::fs::copy(&existing, existing.with_extension("json.bak"))?;
+        }
+    }
+    let mut store = storage::open_store(target)?;
+    store.save(&stats)?;
+
+    // Re-read the file so CLI overrides like --work are not persisted.
+    let path = get_path("config.json");
+    let mut config = load_json::<Config>(&path)?.unwrap_or_default();
+    config.storage = target;
+    save_json(&path, &config)?;
+
+    println!(
+        "Copied {} sessions and {} notes; rtimer now stores statistics in {}",
+        stats.session_history.len(),
+        stats.notes.len(),
+        get_path(target.file_name()).display(),
+    );
+    Ok(())
+}
+
+fn print_response(resp: &Response, running: bool, json: bool) -> Result<()> {
+    if !resp.ok {
+        return Err(resp.error.clone().unwrap_or_else(|| "request failed".into()).into());
+    }
+    let Some(status) = &resp.status else {
+        return Err("daemon sent no status".into());
+    };
+
+    if json {
+        println!("{}", serde_json::to_string(&CliStatus { running, status })?);
+    } else {
+        println!("{}", describe(status, running));
+    }
+    Ok(())
+}
+
+fn describe(status: &Status, running: bool) -> String {
+    let secs = status.remaining_secs as u64;
+    let state = if status.suspended_secs.is_some() {
+        "💤 paused after suspend"
+    } else if status.paused {
+        "⏸ paused"
+    } else {
+        "▶ running"
+    };
+    let mut line = format!(
+        "{}  {:02}:{:02}  {}  •  session {} of {}  •  {} completed today",
+        status.phase_name,
+        secs / 60,
+        secs % 60,
+        state,
+        ((status.session_count.max(1) - 1) % status.sessions_before_long_break.max(1)) + 1,
+        status.sessions_before_long_break,
+        status.sessions_today,
+    );
+    if !running {
+        line.push_str("  (no daemon running; continue with `rtimer --resume` or `rtimer daemon --resume`)");
+    }
+    line
+}
@@ -0,0 +1,498 @@
+use crate::storage::{get_path, save_json};
+use crate::{parse_tags, AppState, Config, InterruptionKind, Note, Phase, Result, Statistics, PhaseClock, Task, TICK_RATE};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{fs::{DirBuilderExt, MetadataExt}, net::{UnixListener, UnixStream}},
+    path::PathBuf,
+    sync::mpsc,
+    time::{Duration, Instant, SystemTime},
+};
+
+// ============================================================================
+// Protocol
+// ============================================================================
+//
+// One JSON object per line in each direction. Every request is answered with
+// exactly one response carrying the timer status after the request ran, e.g.
+//
+//   → {"cmd":"skip"}
+//   ← {"ok":true,"status":{"phase":"short_break",...}}
+
+const SYNC_INTERVAL: Duration = Duration::from_millis(250);
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub(crate) enum Request {
+    Status,
+    Snapshot,
+    Pause,
+    Resume,
+    Toggle,
+    Skip,
+    Reset,
+    /// Begin a fresh focus session, optionally overriding durations (minutes)
+    /// for as long as the timer keeps running.
+    Start {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        work: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        rest: Option<f64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        long_break: Option<f64>,
+    },
+    ResolveSuspend { choice: SuspendChoice },
+    /// Sets why the last pause in the current phase happened.
+    DescribeInterruption {
+        kind: Option<InterruptionKind>,
+        #[serde(default)]
+        reason: String,
+    },
+    AddNote { content: String },
+    AddTask { name: String, estimate: u32 },
+    /// Changes whichever of the fields are given.
+    UpdateTask {
+        id: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        estimate: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        done: Option<bool>,
+    },
+    DeleteTask { id: u64 },
+    /// Attributes work sessions to a task from now on, or to none.
+    SelectTask { id: Option<u64> },
+    /// Labels new work sessions and notes with a project and tags.
+    SetLabel {
+        project: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    EditNote { index: usize, content: String },
+    DeleteNote { index: usize },
+    Configure { config: Config },
+    Shutdown,
+}
+
+/// Answer to the "welcome back" prompt after a suspend was detected.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum SuspendChoice {
+    Count,
+    Discard,
+    StayPaused,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Status {
+    pub(crate) phase: String,
+    pub(crate) phase_name: String,
+    pub(crate) remaining_secs: f64,
+    pub(crate) total_secs: f64,
+    pub(crate) paused: bool,
+    pub(crate) session_count: u32,
+    pub(crate) sessions_before_long_break: u32,
+    pub(crate) sessions_today: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) suspended_secs: Option<u64>,
+    pub(crate) revision: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Snapshot {
+    pub(crate) status: Status,
+    pub(crate) config: Config,
+    pub(crate) stats: Statistics,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub(crate) struct Response {
+    pub(crate) ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) status: Option<Status>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) snapshot: Option<Snapshot>,
+}
+
+impl Response {
+    fn ok(status: Status) -> Self {
+        Self { ok: true, status: Some(status), ..Default::default() }
+    }
+
+    fn error(msg: impl Into<String>) -> Self {
+        Self { ok: false, error: Some(msg.into()), ..Default::default() }
+    }
+}
+
+/// `$XDG_RUNTIME_DIR/rtimer.sock`, or without a runtime directory a socket
+/// in a private `rtimer-$UID` directory under /tmp. The directory is created
+/// 0700 and must belong to us, so another user can't bind there first and
+/// pose as the daemon.
+pub(crate) fn socket_path() -> io::Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+        return Ok(PathBuf::from(dir).join("rtimer.sock"));
+    }
+    let uid = unsafe { libc::getuid() };
+    let dir = std::env::temp_dir().join(format!("rtimer-{}", uid));
+    match fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+        _ => {}
+    }
+    let meta = fs::symlink_metadata(&dir)?;
+    if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+        return Err(io::Error::other(format!("{} is not a private directory owned by you", dir.display())));
+    }
+    Ok(dir.join("rtimer.sock"))
+}
+
+// ============================================================================
+// Request Handling
+// ============================================================================
+
+impl AppState {
+    pub(crate) fn status(&self) -> Status {
+        Status {
+            phase: self.phase.to_str().into(),
+            phase_name: self.phase.name().into(),
+            remaining_secs: self.time_remaining().as_secs_f64(),
+            total_secs: self.total_duration().as_secs_f64(),
+            paused: self.paused(),
+            session_count: self.session_count,
+            sessions_before_long_break: self.sessions_before_long_break,
+            sessions_today: self.stats.sessions_today(),
+            suspended_secs: self.pending_suspend.map(|d| d.as_secs()),
+            revision: self.revision,
+        }
+    }
+
+    /// Applies a request to the local timer. This is what the daemon runs for
+    /// every client request, and what the TUI runs when no daemon is around.
+    pub(crate) fn handle_request(&mut self, req: Request) -> Response {
+        let now = SystemTime::now();
+        let mutates = !matches!(req, Request::Status | Request::Snapshot | Request::Shutdown);
+
+        match req {
+            Request::Status | Request::Shutdown => {}
+            Request::Snapshot => {
+                return Response {
+                    snapshot: Some(Snapshot {
+                        status: self.status(),
+                        config: self.config(),
+                        stats: self.stats.clone(),
+                    }),
+                    ..Response::ok(self.status())
+                };
+            }
+            Request::Pause => self.pause(now),
+            Request::Resume => {
+                if self.pending_suspend.is_some() {
+                    self.resolve_suspend(SuspendChoice::Discard);
+                } else {
+                    self.resume(now);
+                }
+            }
+            Request::Toggle => self.toggle_pause(),
+            Request::Skip => self.next_phase(),
+            Request::Reset => self.reset_timer(),
+            Request::Start { work, rest, long_break } => {
+                // Log whatever was running before starting over.
+                self.reset_timer();
+                if let Some(m) = work { self.work_duration = Duration::from_secs_f64(m * 60.0); }
+                if let Some(m) = rest { self.rest_duration = Duration::from_secs_f64(m * 60.0); }
+                if let Some(m) = long_break { self.long_break_duration = Duration::from_secs_f64(m * 60.0); }
+                self.phase = Phase::Work;
+                self.pending_suspend = None;
+            }
+            Request::ResolveSuspend { choice } => self.resolve_suspend(choice),
+            Request::DescribeInterruption { kind, reason } => match self.interruptions.last_mut() {
+                Some(interruption) => {
+                    interruption.kind = kind;
+                    interruption.reason = reason;
+                }
+                None => return Response::error("no interruption in the current phase"),
+            },
+            Request::AddNote { content } => {
+                self.stats.notes.push(Note {
+                    timestamp: chrono::Local::now().to_rfc3339(),
+                    tags: parse_tags(&content),
+                    content,
+                    phase: self.phase.to_str().into(),
+                    project: self.stats.active_project.clone(),
+                });
+                self.needs_save = true;
+            }
+            Request::EditNote { index, content } => match self.stats.notes.get_mut(index) {
+                Some(note) => {
+                    note.tags = parse_tags(&content);
+                    note.content = content;
+                    self.needs_save = true;
+                }
+                None => return Response::error(format!("no note at index {}", index)),
+            },
+            Request::DeleteNote { index } => {
+                if index >= self.stats.notes.len() {
+                    return Response::error(format!("no note at index {}", index));
+                }
+                self.stats.notes.remove(index);
+                self.needs_save = true;
+            }
+            Request::AddTask { name, estimate } => {
+                let id = self.stats.new_task_id();
+                self.stats.tasks.push(Task {
+                    id,
+                    name,
+                    estimate: estimate.max(1),
+                    done: false,
+                    created: chrono::Local::now().to_rfc3339(),
+                });
+                self.needs_save = true;
+            }
+            Request::UpdateTask { id, name, estimate, done } => {
+                let Some(task) = self.stats.tasks.iter_mut().find(|t| t.id == id) else {
+                    return Response::error(format!("no task with id {}", id));
+                };
+                if let Some(name) = name { task.name = name; }
+                if let Some(estimate) = estimate { task.estimate = estimate.max(1); }
+                if let Some(done) = done { task.done = done; }
+                if task.done && self.stats.active_task == Some(id) {
+                    self.stats.active_task = None;
+                }
+                self.needs_save = true;
+            }
+            Request::DeleteTask { id } => {
+                if self.stats.task(id).is_none() {
+                    return Response::error(format!("no task with id {}", id));
+                }
+                self.stats.tasks.retain(|t| t.id != id);
+                if self.stats.active_task == Some(id) {
+                    self.stats.active_task = None;
+                }
+                self.needs_save = true;
+            }
+            Request::SelectTask { id } => {
+                if let Some(id) = id && self.stats.task(id).is_none() {
+                    return Response::error(format!("no task with id {}", id));
+                }
+                self.stats.active_task = id;
+                self.needs_save = true;
+            }
+            Request::SetLabel { project, tags } => {
+                self.stats.active_project = project;
+                self.stats.active_tags = tags;
+                self.needs_save = true;
+            }
+            Request::Configure { config } => {
+                self.apply_config(&config);
+                if let Err(e) = save_json(&get_path("config.json"), &config) {
+                    return Response::error(format!("could not save config: {}", e));
+                }
+            }
+        }
+
+        if mutates {
+            self.revision += 1;
+        }
+        Response::ok(self.status())
+    }
+
+    /// Runs a request against whichever timer this UI is driving: the daemon
+    /// when attached to one, otherwise the local state.
+    pub(crate) fn dispatch(&mut self, req: Request) {
+        match self.remote.as_mut() {
+            None => {
+                self.handle_request(req);
+            }
+            Some(remote) => {
+                remote.request(&req);
+                remote.last_sync = None;
+                self.sync_remote();
+            }
+        }
+    }
+
+    /// Mirrors the daemon's timer, fetching stats and config only when the
+    /// daemon reports a new revision.
+    pub(crate) fn sync_remote(&mut self) {
+        let Some(remote) = self.remote.as_mut() else { return };
+        if remote.last_sync.is_some_and(|t| t.elapsed() < SYNC_INTERVAL) {
+            return;
+        }
+        remote.last_sync = Some(Instant::now());
+
+        let Some(status) = remote.request(&Request::Status).and_then(|r| r.status) else { return };
+        if remote.revision == Some(status.revision) {
+            self.apply_status(&status);
+            return;
+        }
+
+        let Some(snapshot) = remote.request(&Request::Snapshot).and_then(|r| r.snapshot) else { return };
+        remote.revision = Some(snapshot.status.revision);
+        self.apply_config(&snapshot.config);
+        self.stats = snapshot.stats;
+        self.selected_note_index = match self.selected_note_index {
+            _ if self.stats.notes.is_empty() => None,
+            Some(idx) => Some(idx.min(self.stats.notes.len() - 1)),
+            None => Some(self.stats.notes.len() - 1),
+        };
+        self.apply_status(&snapshot.status);
+    }
+
+    fn apply_status(&mut self, status: &Status) {
+        let elapsed = Duration::from_secs_f64((status.total_secs - status.remaining_secs).max(0.0));
+        self.phase = Phase::from_str(&status.phase);
+        self.session_count = status.session_count;
+        self.clock = PhaseClock::start(SystemTime::now(), elapsed, status.paused);
+        self.pending_suspend = status.suspended_secs.map(Duration::from_secs);
+    }
+}
+
+// ============================================================================
+// Client
+// ============================================================================
+
+pub(crate) struct Client {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Client {
+    pub(crate) fn connect() -> io::Result<Self> {
+        let stream = UnixStream::connect(socket_path()?)?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        Ok(Self { reader: BufReader::new(stream.try_clone()?), writer: stream })
+    }
+
+    pub(crate) fn request(&mut self, req: &Request) -> Result<Response> {
+        let mut line = serde_json::to_string(req)?;
+        line.push('\n');
+        self.writer.write_all(line.as_bytes())?;
+
+        let mut reply = String::new();
+        if self.reader.read_line(&mut reply)? == 0 {
+            return Err("daemon closed the connection".into());
+        }
+        Ok(serde_json::from_str(&reply)?)
+    }
+}
+
+/// Connection used by the TUI while attached to a daemon. Reconnects lazily
+/// if the daemon goes away and comes back.
+pub(crate) struct Remote {
+    client: Option<Client>,
+    last_sync: Option<Instant>,
+    revision: Option<u64>,
+}
+
+impl Remote {
+    pub(crate) fn connect() -> Option<Self> {
+        Client::connect().ok().map(|client| Self {
+            client: Some(client),
+            last_sync: None,
+            revision: None,
+        })
+    }
+
+    pub(crate) fn connected(&self) -> bool {
+        self.client.is_some()
+    }
+
+    fn request(&mut self, req: &Request) -> Option<Response> {
+        if self.client.is_none() {
+            self.client = Client::connect().ok();
+            self.revision = None;
+        }
+        match self.client.as_mut()?.request(req) {
+            Ok(resp) => Some(resp),
+            Err(_) => {
+                self.client = None;
+                None
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Server
+// ============================================================================
+
+type Job = (Request, mpsc::Sender<Response>);
+
+pub(crate) fn run_daemon(mut app: AppState) -> Result<()> {
+    let path = socket_path()?;
+    if UnixStream::connect(&path).is_ok() {
+        return Err(format!("an rtimer daemon is already listening on {}", path.display()).into());
+    }
+    let _ = fs::remove_file(&path);
+    // Create the socket 0600 rather than tightening it after bind, which
+    // would leave it open to others for a moment.
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(&path);
+    unsafe { libc::umask(umask) };
+    let listener = listener?;
+    eprintln!("rtimer daemon listening on {}", path.display());
+
+    let (tx, rx) = mpsc::channel::<Job>();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let tx = tx.clone();
+            std::thread::spawn(move || serve_client(stream, tx));
+        }
+    });
+
+    loop {
+        match rx.recv_timeout(TICK_RATE) {
+            Ok((req, reply)) => {
+                let shutdown = matches!(req, Request::Shutdown);
+                let _ = reply.send(app.handle_request(req));
+                if shutdown {
+                    break;
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+        app.update();
+    }
+
+    if let Err(e) = app.record_quit() {
+        eprintln!("⚠ Timewarrior: {}", e);
+    }
+    app.save_on_quit();
+    let _ = fs::remove_file(&path);
+    Ok(())
+}
+
+fn serve_client(stream: UnixStream, jobs: mpsc::Sender<Job>) {
+    let Ok(read_half) = stream.try_clone() else { return };
+    let mut writer = stream;
+
+    for line in BufReader::new(read_half).lines() {
+        let Ok(line) = line else { break };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let resp = match serde_json::from_str::<Request>(&line) {
+            Ok(req) => {
+                let (reply_tx, reply_rx) = mpsc::channel();
+                if jobs.send((req, reply_tx)).is_err() {
+                    break;
+                }
+                reply_rx.recv().unwrap_or_else(|_| Response::error("daemon is shutting down"))
+            }
+            Err(e) => Response::error(format!("invalid request: {}", e)),
+        };
+
+        let Ok(mut out) = serde_json::to_string(&resp) else { break };
+        out.push('\n');
+        if writer.write_all(out.as_bytes()).is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,368 @@
+use crate::commands::latest_stats;
+use crate::storage::{get_path, write_atomic};
+use crate::{ics, AppState, EndReason, Result, SessionRecord, Statistics};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub(crate) enum ExportTable {
+    /// Every recorded phase with its timing, end reason and labels
+    Sessions,
+    /// Notes with the phase and project they were written in
+    Notes,
+    /// Tasks with their estimate and the pomodoros spent on them
+    Tasks,
+    /// Work totals per project and per #tag
+    Labels,
+}
+
+impl ExportTable {
+    const ALL: [Self; 4] = [Self::Sessions, Self::Notes, Self::Tasks, Self::Labels];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Sessions => "sessions",
+            Self::Notes => "notes",
+            Self::Tasks => "tasks",
+            Self::Labels => "labels",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+pub(crate) enum ExportFormat {
+    /// RFC 4180 CSV with a header row
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+    /// iCalendar events for completed sessions, for calendar apps
+    Ics,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Jsonl => "jsonl",
+            Self::Ics => "ics",
+        }
+    }
+}
+
+/// A row of an exported table. CSV and JSON Lines carry the same columns:
+/// the row's fields, in `COLUMNS` order for CSV.
+trait Row: Serialize {
+    const COLUMNS: &'static [&'static str];
+}
+
+/// A session as exported, and as `rtimer import rtimer` reads it back.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SessionRow {
+    started: Option<String>,
+    ended: String,
+    phase: String,
+    planned_min: u64,
+    focused_secs: u64,
+    paused_secs: u64,
+    end_reason: EndReason,
+    #[serde(default)]
+    interruptions: usize,
+    #[serde(default)]
+    interrupted_secs: u64,
+    /// The task's name; ids only mean something in the file they came from.
+    #[serde(default)]
+    task: Option<String>,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl Row for SessionRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "started", "ended", "phase", "planned_min", "focused_secs", "paused_secs", "end_reason",
+        "interruptions", "interrupted_secs", "task", "project", "tags",
+    ];
+}
+
+impl SessionRow {
+    fn new(stats: &Statistics, s: &SessionRecord) -> Self {
+        Self {
+            started: s.started.clone(),
+            ended: s.timestamp.clone(),
+            phase: s.phase_type.clone(),
+            planned_min: s.duration,
+            focused_secs: s.elapsed_secs,
+            paused_secs: s.paused_secs,
+            end_reason: s.end_reason,
+            interruptions: s.interruptions.len(),
+            interrupted_secs: s.interruptions.iter().map(|i| i.duration_secs).sum(),
+            task: s.task.and_then(|id| stats.task(id)).map(|t| t.name.clone()),
+            project: s.project.clone(),
+            tags: s.tags.clone(),
+        }
+    }
+
+    /// The session this row was exported from, less its task and the
+    /// details of its interruptions.
+    pub(crate) fn into_record(self) -> SessionRecord {
+        SessionRecord {
+            started: self.started,
+            timestamp: self.ended,
+            phase_type: self.phase,
+            duration: self.planned_min,
+            elapsed_secs: self.focused_secs,
+            paused_secs: self.paused_secs,
+            end_reason: self.end_reason,
+            interruptions: Vec::new(),
+            task: None,
+            project: self.project,
+            tags: self.tags,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct NoteRow<'a> {
+    timestamp: &'a str,
+    phase: &'a str,
+    content: &'a str,
+    project: Option<&'a str>,
+    tags: &'a [String],
+}
+
+impl Row for NoteRow<'_> {
+    const COLUMNS: &'static [&'static str] = &["timestamp", "phase", "content", "project", "tags"];
+}
+
+#[derive(Serialize)]
+struct TaskRow<'a> {
+    id: u64,
+    name: &'a str,
+    estimate: u32,
+    pomodoros: u32,
+    done: bool,
+    created: &'a str,
+}
+
+impl Row for TaskRow<'_> {
+    const COLUMNS: &'static [&'static str] = &["id", "name", "estimate", "pomodoros", "done", "created"];
+}
+
+/// Totals for one project or tag in the labels table.
+#[derive(Serialize)]
+struct LabelRow<'a> {
+    kind: &'static str,
+    name: &'a str,
+    sessions: u32,
+    focus_secs: u64,
+}
+
+impl Row for LabelRow<'_> {
+    const COLUMNS: &'static [&'static str] = &["kind", "name", "sessions", "focus_secs"];
+}
+
+/// Writes one table to `output`, or to stdout when it is `None` or `-`.
+pub(crate) fn run(
+    app: AppState,
+    table: ExportTable,
+    format: ExportFormat,
+    output: Option<&Path>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+) -> Result<()> {
+    if let (Some(from), Some(to)) = (from, to)
+        && from > to
+    {
+        return Err(format!("--from {} is after --to {}", from, to).into());
+    }
+    let stats = within(latest_stats(app.stats)?, from, to);
+    let mut out = Vec::new();
+    let rows = match format {
+        ExportFormat::Ics if table != ExportTable::Sessions => {
+            return Err("iCalendar export only covers sessions".into());
+        }
+        ExportFormat::Ics => {
+            out = ics::calendar(&stats).into_bytes();
+            stats.session_history.iter().filter(|s| s.completed()).count()
+        }
+        _ => write_table(&stats, table, format, &mut out)?,
+    };
+    match output {
+        Some(path) if path != Path::new("-") => {
+            write_atomic(path, &out)?;
+            eprintln!("Exported {} {} to {}", rows, table.name(), path.display());
+        }
+        _ => io::stdout().write_all(&out)?,
+    }
+    Ok(())
+}
+
+/// Writes every table as `<table>_export.csv` next to the statistics, for
+/// the TUI's export key, and returns the directory they went to.
+pub(crate) fn all_to_data_dir(stats: &Statistics) -> io::Result<PathBuf> {
+    let mut dir = PathBuf::new();
+    for table in ExportTable::ALL {
+        let path = get_path(&format!("{}_export.{}", table.name(), ExportFormat::Csv.extension()));
+        let mut out = Vec::new();
+        write_table(stats, table, ExportFormat::Csv, &mut out)?;
+        write_atomic(&path, &out)?;
+        dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    }
+    Ok(dir)
+}
+
+/// Keeps only the sessions and notes from days between `from` and `to`.
+fn within(mut stats: Statistics, from: Option<NaiveDate>, to: Option<NaiveDate>) -> Statistics {
+    let keep = |date: Option<NaiveDate>| {
+        date.is_some_and(|d| from.is_none_or(|from| d >= from) && to.is_none_or(|to| d <= to))
+    };
+    if from.is_some() || to.is_some() {
+        stats.session_history.retain(|s| keep(s.date()));
+        stats.notes.retain(|n| keep(chrono::DateTime::parse_from_rfc3339(&n.timestamp).ok().map(|t| t.date_naive())));
+    }
+    stats
+}
+
+/// Writes `table` and returns how many rows it had.
+fn write_table(stats: &Statistics, table: ExportTable, format: ExportFormat, out: &mut impl Write) -> io::Result<usize> {
+    let mut rows = Rows { out, format, count: 0 };
+    match table {
+        ExportTable::Sessions => {
+            rows.header::<SessionRow>()?;
+            for s in &stats.session_history {
+                rows.row(&SessionRow::new(stats, s))?;
+            }
+        }
+        ExportTable::Notes => {
+            rows.header::<NoteRow>()?;
+            for n in &stats.notes {
+                rows.row(&NoteRow {
+                    timestamp: &n.timestamp,
+                    phase: &n.phase,
+                    content: &n.content,
+                    project: n.project.as_deref(),
+                    tags: &n.tags,
+                })?;
+            }
+        }
+        ExportTable::Tasks => {
+            rows.header::<TaskRow>()?;
+            for t in &stats.tasks {
+                rows.row(&TaskRow {
+                    id: t.id,
+                    name: &t.name,
+                    estimate: t.estimate,
+                    pomodoros: stats.task_pomodoros(t.id),
+                    done: t.done,
+                    created: &t.created,
+                })?;
+            }
+        }
+        ExportTable::Labels => {
+            rows.header::<LabelRow>()?;
+            let (projects, tags) = stats.label_totals();
+            for (kind, totals) in [("project", projects), ("tag", tags)] {
+                for (name, t) in totals {
+                    rows.row(&LabelRow { kind, name, sessions: t.work_sessions, focus_secs: t.work_secs })?;
+                }
+            }
+        }
+    }
+    Ok(rows.count)
+}
+
+/// Writes rows either as CSV records or as JSON objects.
+struct Rows<'a, W> {
+    out: &'a mut W,
+    format: ExportFormat,
+    count: usize,
+}
+
+impl<W: Write> Rows<'_, W> {
+    fn header<R: Row>(&mut self) -> io::Result<()> {
+        match self.format {
+            ExportFormat::Csv => self.csv(R::COLUMNS.iter().map(|c| c.to_string())),
+            ExportFormat::Jsonl => Ok(()),
+            ExportFormat::Ics => unreachable!("calendars are written by ics::calendar"),
+        }
+    }
+
+    fn row<R: Row>(&mut self, row: &R) -> io::Result<()> {
+        self.count += 1;
+        match self.format {
+            ExportFormat::Csv => {
+                let value = serde_json::to_value(row)?;
+                self.csv(R::COLUMNS.iter().map(|c| csv_field(&value[c])))
+            }
+            ExportFormat::Jsonl => {
+                serde_json::to_writer(&mut *self.out, row)?;
+                self.out.write_all(b"\n")
+            }
+            ExportFormat::Ics => unreachable!("calendars are written by ics::calendar"),
+        }
+    }
+
+    /// One RFC 4180 record: every field quoted, quotes doubled, CRLF ended.
+    fn csv(&mut self, fields: impl Iterator<Item = String>) -> io::Result<()> {
+        let line: Vec<String> = fields.map(|f| format!("\"{}\"", f.replace('"', "\"\""))).collect();
+        write!(self.out, "{}\r\n", line.join(","))
+    }
+}
+
+/// A JSON value as a CSV field: nothing for null, lists space-separated.
+fn csv_field(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(csv_field).collect::<Vec<_>>().join(" "),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::sample;
+
+    fn export(table: ExportTable, format: ExportFormat) -> String {
+        let mut out = Vec::new();
+        write_table(&sample(), table, format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn csv_and_jsonl_have_the_same_columns() {
+        for table in ExportTable::ALL {
+            let csv = export(table, ExportFormat::Csv);
+            let mut header: Vec<&str> = csv.lines().next().unwrap().split(',').map(|c| c.trim_matches('"')).collect();
+            header.sort();
+            for line in export(table, ExportFormat::Jsonl).lines() {
+                let row: serde_json::Map<String, Value> = serde_json::from_str(line).unwrap();
+                assert_eq!(row.keys().map(String::as_str).collect::<Vec<_>>(), header, "{}", table.name());
+            }
+        }
+    }
+
+    #[test]
+    fn sessions_carry_the_task_name() {
+        let csv = export(ExportTable::Sessions, ExportFormat::Csv);
+        assert!(csv.lines().nth(1).unwrap().ends_with(r#""2","120","Write draft","thesis","writing deep""#));
+        let jsonl = export(ExportTable::Sessions, ExportFormat::Jsonl);
+        let row: SessionRow = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(row.task.as_deref(), Some("Write draft"));
+        let record = row.into_record();
+        assert_eq!((record.paused_secs, record.task), (120, None));
+    }
+
+    #[test]
+    fn csv_quotes_fields() {
+        let csv = export(ExportTable::Notes, ExportFormat::Csv);
+        assert_eq!(csv.lines().nth(1).unwrap(), r#""2026-10-01T09:15:00+02:00","work","outline ""intro"", #chapter2","thesis","chapter2""#);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::{EndReason, SessionRecord, Statistics};
+use chrono::{DateTime, FixedOffset, Utc};
+use std::fmt::Write;
+
+const UTC_FMT: &str = "%Y%m%dT%H%M%SZ";
+
+/// An iCalendar file with one event per completed phase. Event UIDs are
+/// derived from the start time, so calendars that subscribe to a file
+/// rewritten after every phase see updates rather than duplicates.
+pub(crate) fn calendar(stats: &Statistics) -> String {
+    let mut out = String::new();
+    line(&mut out, "BEGIN:VCALENDAR");
+    line(&mut out, "VERSION:2.0");
+    line(&mut out, "PRODID:-//rtimer//Focus sessions//EN");
+    line(&mut out, "CALSCALE:GREGORIAN");
+    for s in stats.session_history.iter().filter(|s| s.end_reason == EndReason::Completed) {
+        event(&mut out, stats, s);
+    }
+    line(&mut out, "END:VCALENDAR");
+    out
+}
+
+fn event(out: &mut String, stats: &Statistics, s: &SessionRecord) {
+    let Ok(end) = DateTime::parse_from_rfc3339(&s.timestamp) else { return };
+    let start = s.started.as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .unwrap_or_else(|| end - chrono::Duration::seconds((s.elapsed_secs + s.paused_secs) as i64));
+    let utc = |t: DateTime<FixedOffset>| t.with_timezone(&Utc).format(UTC_FMT).to_string();
+
+    let mut summary = match s.phase_type.as_str() {
+        "Work" => match s.task.and_then(|id| stats.task(id)) {
+            Some(task) => format!("Focus: {}", task.name),
+            None => "Focus".to_string(),
+        },
+        phase => phase.to_string(),
+    };
+    if let Some(project) = &s.project {
+        let _ = write!(summary, " [{}]", project);
+    }
+
+    let mut description = format!("Focused {} of {} min", crate::format_secs(s.elapsed_secs), s.duration);
+    if s.paused_secs > 0 {
+        let _ = write!(description, ", paused {}", crate::format_secs(s.paused_secs));
+    }
+    let notes: Vec<&str> = stats.notes.iter()
+        .filter(|n| DateTime::parse_from_rfc3339(&n.timestamp).is_ok_and(|t| start <= t && t <= end))
+        .map(|n| n.content.as_str())
+        .collect();
+    if !notes.is_empty() {
+        description.push_str("\n\nNotes:");
+        for note in notes {
+            let _ = write!(description, "\n- {}", note);
+        }
+    }
+
+    line(out, "BEGIN:VEVENT");
+    line(out, &format!("UID:{}-{}@rtimer", utc(start), s.phase_type.to_lowercase().replace(' ', "-")));
+    line(out, &format!("DTSTAMP:{}", utc(end)));
+    line(out, &format!("DTSTART:{}", utc(start)));
+    line(out, &format!("DTEND:{}", utc(end)));
+    line(out, &format!("SUMMARY:{}", escape(&summary)));
+    line(out, &format!("DESCRIPTION:{}", escape(&description)));
+    let categories: Vec<String> = s.project.iter().chain(&s.tags).map(|c| escape(c)).collect();
+    if !categories.is_empty() {
+        line(out, &format!("CATEGORIES:{}", categories.join(",")));
+    }
+    line(out, "TRANSP:OPAQUE");
+    line(out, "END:VEVENT");
+}
+
+/// Escapes a TEXT value (RFC 5545 section 3.3.11).
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\r', "")
+        .replace('\n', "\\n")
+}
+
+/// Appends a content line, folded at 75 octets and ended with CRLF.
+fn line(out: &mut String, content: &str) {
+    let mut width = 0;
+    for c in content.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The sample with its work session's task and note replaced.
+    fn sample(task: &str, note: &str) -> Statistics {
+        let mut stats = crate::testing::sample();
+        stats.tasks[1].name = task.into();
+        stats.notes[0].content = note.into();
+        stats
+    }
+
+    /// The calendar's logical lines, unfolded.
+    fn unfold(ics: &str) -> Vec<String> {
+        ics.replace("\r\n ", "").split("\r\n").map(String::from).collect()
+    }
+
+    #[test]
+    fn folds_at_75_octets_between_characters() {
+        let task = "Übersetzung prüfen – 日本語のドキュメントを読む ".repeat(4);
+        let ics = calendar(&sample(&task, "short"));
+
+        for physical in ics.split("\r\n") {
+            assert!(physical.len() <= 75, "{:?} is {} octets", physical, physical.len());
+        }
+        assert!(unfold(&ics).contains(&format!("SUMMARY:Focus: {} [thesis]", task)));
+    }
+
+    #[test]
+    fn escapes_text_values() {
+        let ics = calendar(&sample("a;b", "semi; comma, back\\slash\r\nnext"));
+        let lines = unfold(&ics);
+
+        assert!(lines.contains(&"SUMMARY:Focus: a\\;b [thesis]".to_string()));
+        let description = lines.iter().find(|l| l.starts_with("DESCRIPTION:")).unwrap();
+        assert!(description.ends_with(r"- semi\; comma\, back\\slash\nnext"), "{}", description);
+    }
+}
@@ -0,0 +1,483 @@
+use crate::daemon::Client;
+use crate::export::SessionRow;
+use crate::storage::{self, JsonStorage, Storage};
+use crate::{timewarrior, AppState, EndReason, Note, Result, SessionRecord, Statistics};
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, TimeZone};
+use std::path::{Path, PathBuf};
+
+/// Sessions this close together at both ends are taken to be the same one.
+const SAME_SESSION_SLACK: i64 = 60;
+/// Tag on sessions made from another tool's time records, which say
+/// nothing about whether a pomodoro was finished.
+const IMPORTED_TAG: &str = "imported";
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub(crate) enum ImportSource {
+    /// Timewarrior data files (`data/*.data` in its database directory)
+    Timewarrior,
+    /// Any CSV with a header row; see the column options
+    Csv,
+    /// rtimer's stats.json, or `rtimer export sessions --format jsonl` output
+    Rtimer,
+}
+
+/// Which CSV columns hold what, by header name.
+#[derive(Clone, clap::Args)]
+pub(crate) struct CsvColumns {
+    /// Column with the start time
+    #[arg(long, value_name = "NAME", default_value = "start")]
+    start_column: String,
+    /// Column with the end time
+    #[arg(long, value_name = "NAME", default_value = "end")]
+    end_column: String,
+    /// Column with the length in minutes, for files that lack a start or end
+    #[arg(long, value_name = "NAME")]
+    duration_column: Option<String>,
+    /// Column saying whether a row was work or a break; rows without one are work
+    #[arg(long, value_name = "NAME")]
+    phase_column: Option<String>,
+    /// Column with the project
+    #[arg(long, value_name = "NAME")]
+    project_column: Option<String>,
+    /// Column with tags, separated by spaces or commas
+    #[arg(long, value_name = "NAME")]
+    tags_column: Option<String>,
+    /// strftime format of the times, read as local time; RFC 3339 and
+    /// `YYYY-MM-DD HH:MM[:SS]` are understood without it
+    #[arg(long, value_name = "FORMAT")]
+    time_format: Option<String>,
+}
+
+/// What was read, before deduplication.
+#[derive(Default)]
+struct Imported {
+    sessions: Vec<SessionRecord>,
+    notes: Vec<Note>,
+    unreadable: usize,
+}
+
+/// Reads history from another tool and merges what isn't recorded yet
+/// into the session log.
+pub(crate) fn run(
+    app: AppState,
+    source: ImportSource,
+    paths: &[PathBuf],
+    columns: &CsvColumns,
+    tags: &[String],
+    dry_run: bool,
+) -> Result<()> {
+    if Client::connect().is_ok() {
+        return Err("a daemon is running; stop it before importing".into());
+    }
+    let _lock = storage::lock_data_dir()?.ok_or_else(storage::busy_message)?;
+
+    let paths = match (source, paths.is_empty()) {
+        (ImportSource::Timewarrior, true) => timewarrior_data_files()?,
+        (_, true) => return Err("name the file(s) to import".into()),
+        (_, false) => paths.to_vec(),
+    };
+    let mut imported = Imported::default();
+    for path in &paths {
+        match source {
+            ImportSource::Timewarrior => read_timewarrior(path, &mut imported)?,
+            ImportSource::Csv => read_csv(path, columns, &mut imported)?,
+            ImportSource::Rtimer => read_rtimer(path, &mut imported)?,
+        }
+    }
+    let wanted: Vec<String> = tags.iter().map(|t| tag(t)).collect();
+    imported.sessions.retain(|s| wanted.iter().all(|t| s.tags.contains(t)));
+
+    let mut stats = app.stats;
+    let read = imported.sessions.len();
+    let mut added: Vec<SessionRecord> = Vec::new();
+    for s in imported.sessions {
+        if !stats.session_history.iter().chain(&added).any(|existing| same_session(existing, &s)) {
+            added.push(s);
+        }
+    }
+    let notes: Vec<Note> = imported.notes.into_iter()
+        .filter(|n| !stats.notes.iter().any(|e| e.timestamp == n.timestamp && e.content == n.content))
+        .collect();
+
+    println!(
+        "Read {} from {}{}; {} already recorded",
+        count(read, "session"),
+        count(paths.len(), "file"),
+        match imported.unreadable {
+            0 => String::new(),
+            n => format!(" ({} skipped as unreadable)", count(n, "line")),
+        },
+        read - added.len(),
+    );
+    let focus: u64 = added.iter().filter(|s| s.is_work()).map(|s| s.elapsed_secs).sum();
+    let span = match (added.iter().filter_map(|s| s.date()).min(), added.iter().filter_map(|s| s.date()).max()) {
+        (Some(first), Some(last)) => format!(", {} to {}", first, last),
+        _ => String::new(),
+    };
+    println!(
+        "{} {} ({:.1}h focus{}) and {}",
+        if dry_run { "Would import" } else { "Imported" },
+        count(added.len(), "session"),
+        focus as f64 / 3600.0,
+        span,
+        count(notes.len(), "note"),
+    );
+    if dry_run || (added.is_empty() && notes.is_empty()) {
+        return Ok(());
+    }
+
+    stats.session_history.extend(added);
+    stats.session_history.sort_by_key(|s| DateTime::parse_from_rfc3339(&s.timestamp).ok());
+    stats.notes.extend(notes);
+    stats.notes.sort_by_key(|n| DateTime::parse_from_rfc3339(&n.timestamp).ok());
+    // A freshly opened store writes every session, not only those after
+    // the ones it loaded.
+    storage::open_store(app.storage_backend)?.save(&stats)?;
+    Ok(())
+}
+
+fn count(n: usize, noun: &str) -> String {
+    format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" })
+}
+
+fn same_session(a: &SessionRecord, b: &SessionRecord) -> bool {
+    let close = |x: Option<&str>, y: Option<&str>| match (x.and_then(parse_rfc3339), y.and_then(parse_rfc3339)) {
+        (Some(x), Some(y)) => (x - y).num_seconds().abs() < SAME_SESSION_SLACK,
+        _ => true,
+    };
+    a.is_work() == b.is_work()
+        && close(Some(&a.timestamp), Some(&b.timestamp))
+        && close(a.started.as_deref(), b.started.as_deref())
+}
+
+fn parse_rfc3339(t: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(t).ok()
+}
+
+/// A completed phase from `start` to `end`, recorded in local time like
+/// the sessions rtimer logs itself and tagged as imported.
+fn session(
+    start: DateTime<FixedOffset>,
+    end: DateTime<FixedOffset>,
+    phase: &str,
+    project: Option<String>,
+    mut tags: Vec<String>,
+) -> SessionRecord {
+    let secs = (end - start).num_seconds().max(0) as u64;
+    if phase != "Work" {
+        tags.clear();
+    }
+    if !tags.iter().any(|t| t == IMPORTED_TAG) {
+        tags.push(IMPORTED_TAG.into());
+    }
+    SessionRecord {
+        started: Some(start.with_timezone(&Local).to_rfc3339()),
+        timestamp: end.with_timezone(&Local).to_rfc3339(),
+        phase_type: phase.into(),
+        duration: (secs + 30) / 60,
+        elapsed_secs: secs,
+        paused_secs: 0,
+        end_reason: EndReason::Completed,
+        interruptions: Vec::new(),
+        task: None,
+        project: if phase == "Work" { project } else { None },
+        tags,
+    }
+}
+
+fn tag(name: &str) -> String {
+    name.trim().trim_start_matches('#').to_lowercase().replace(char::is_whitespace, "-")
+}
+
+// ============================================================================
+// Timewarrior
+// ============================================================================
+
+fn timewarrior_data_files() -> Result<Vec<PathBuf>> {
+    let dir = timewarrior::database_dir().ok_or("no Timewarrior database found; set TIMEWARRIORDB or name the data files")?;
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir.join("data"))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "data"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Reads lines like `inc 20261016T090000Z - 20261016T092500Z # work "deep focus"`.
+/// Intervals that are still open are skipped.
+fn read_timewarrior(path: &Path, imported: &mut Imported) -> Result<()> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let (interval, tags) = line.split_once(" # ").unwrap_or((line, ""));
+        let parsed = match interval.split_whitespace().collect::<Vec<_>>()[..] {
+            ["inc", start, "-", end] => parse_timew_time(start).zip(parse_timew_time(end)),
+            ["inc", _] => continue,
+            _ => None,
+        };
+        let Some((start, end)) = parsed else {
+            imported.unreadable += 1;
+            continue;
+        };
+        imported.sessions.push(session(start, end, "Work", None, timewarrior_tags(tags)));
+    }
+    Ok(())
+}
+
+fn parse_timew_time(t: &str) -> Option<DateTime<FixedOffset>> {
+    NaiveDateTime::parse_from_str(t, "%Y%m%dT%H%M%SZ").ok().map(|t| t.and_utc().fixed_offset())
+}
+
+/// Splits Timewarrior's tag list, where tags with spaces are quoted and
+/// quotes within them are escaped with a backslash. The list ends at a
+/// lone `#`, which starts the interval's annotation; that is not imported.
+pub(crate) fn timewarrior_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(first) = chars.next() else { break };
+        let mut name = String::new();
+        if first == '"' {
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => name.extend(chars.next()),
+                    '"' => break,
+                    c => name.push(c),
+                }
+            }
+        } else {
+            name.push(first);
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                name.push(c);
+            }
+            if name == "#" {
+                break;
+            }
+        }
+        let name = tag(&name);
+        if !name.is_empty() && !tags.contains(&name) {
+            tags.push(name);
+        }
+    }
+    tags
+}
+
+// ============================================================================
+// CSV
+// ============================================================================
+
+fn read_csv(path: &Path, columns: &CsvColumns, imported: &mut Imported) -> Result<()> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut records = parse_csv(&text).into_iter();
+    let header = records.next().ok_or_else(|| format!("{} is empty", path.display()))?;
+    let find = |name: &str| {
+        header.iter().position(|h| h.trim().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("{} has no column named {:?}", path.display(), name))
+    };
+    let optional = |name: &Option<String>| name.as_deref().map(find).transpose();
+    let start = find(&columns.start_column).ok();
+    let end = find(&columns.end_column).ok();
+    let duration = optional(&columns.duration_column)?;
+    let (phase, project, tags) = (
+        optional(&columns.phase_column)?,
+        optional(&columns.project_column)?,
+        optional(&columns.tags_column)?,
+    );
+    if [start, end, duration].iter().filter(|c| c.is_some()).count() < 2 {
+        return Err(format!(
+            "{} needs two of the start ({:?}), end ({:?}) and duration columns",
+            path.display(), columns.start_column, columns.end_column,
+        ).into());
+    }
+
+    for record in records {
+        let field = |i: Option<usize>| i.and_then(|i| record.get(i)).map(|f| f.trim()).filter(|f| !f.is_empty());
+        let time = |i| field(i).and_then(|t| parse_time(t, columns.time_format.as_deref()));
+        let minutes = field(duration).and_then(|d| d.parse::<f64>().ok()).map(|m| chrono::Duration::seconds((m * 60.0) as i64));
+        let interval = match (time(start), time(end), minutes) {
+            (Some(s), Some(e), _) => Some((s, e)),
+            (Some(s), None, Some(d)) => Some((s, s + d)),
+            (None, Some(e), Some(d)) => Some((e - d, e)),
+            _ => None,
+        };
+        let Some((s, e)) = interval.filter(|(s, e)| s < e) else {
+            imported.unreadable += 1;
+            continue;
+        };
+        let phase = match field(phase).map(str::to_lowercase) {
+            Some(p) if p.contains("long") => "Long Break",
+            Some(p) if p.contains("break") || p.contains("rest") => "Short Break",
+            _ => "Work",
+        };
+        let tags = field(tags).map_or_else(Vec::new, |t| {
+            t.split([' ', ',']).map(tag).filter(|t| !t.is_empty()).collect()
+        });
+        imported.sessions.push(session(s, e, phase, field(project).map(String::from), tags));
+    }
+    Ok(())
+}
+
+fn parse_time(text: &str, format: Option<&str>) -> Option<DateTime<FixedOffset>> {
+    let local = |t: NaiveDateTime| Local.from_local_datetime(&t).earliest().map(|t| t.fixed_offset());
+    match format {
+        Some(format) => NaiveDateTime::parse_from_str(text, format).ok().and_then(local),
+        None => parse_rfc3339(text).or_else(|| {
+            ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"].iter()
+                .find_map(|f| NaiveDateTime::parse_from_str(text, f).ok())
+                .and_then(local)
+        }),
+    }
+}
+
+/// Splits RFC 4180 CSV into records, honouring quoted fields that contain
+/// commas, doubled quotes and line breaks.
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (quoted, c) {
+            (true, '"') if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            (true, '"') => quoted = false,
+            (true, c) => field.push(c),
+            (false, '"') => quoted = true,
+            (false, ',') => record.push(std::mem::take(&mut field)),
+            (false, '\r') => {}
+            (false, '\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (false, c) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+// ============================================================================
+// rtimer
+// ============================================================================
+
+/// Reads a stats.json (of any version) or the JSON Lines of `rtimer export`.
+/// Task ids only mean something in the file they came from, so they are
+/// dropped.
+fn read_rtimer(path: &Path, imported: &mut Imported) -> Result<()> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let is_stats = serde_json::from_str::<serde_json::Value>(&text)
+        .is_ok_and(|v| v.get("session_history").is_some());
+    let (sessions, notes) = if is_stats {
+        let stats: Statistics = JsonStorage { path: path.to_path_buf() }.load()?;
+        (stats.session_history, stats.notes)
+    } else {
+        let mut sessions = Vec::new();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            match serde_json::from_str::<SessionRow>(line) {
+                Ok(row) => sessions.push(row.into_record()),
+                Err(_) => imported.unreadable += 1,
+            }
+        }
+        (sessions, Vec::new())
+    };
+    imported.sessions.extend(sessions.into_iter().map(|s| SessionRecord { task: None, ..s }));
+    imported.notes.extend(notes);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempPath;
+
+    fn columns() -> CsvColumns {
+        CsvColumns {
+            start_column: "start".into(),
+            end_column: "end".into(),
+            duration_column: None,
+            phase_column: None,
+            project_column: None,
+            tags_column: None,
+            time_format: None,
+        }
+    }
+
+    fn at(t: &str) -> DateTime<FixedOffset> {
+        parse_rfc3339(t).unwrap()
+    }
+
+    #[test]
+    fn csv_quoted_fields() {
+        let records = parse_csv("a,b,c\r\n\"x, y\",\"say \"\"hi\"\"\",\"two\nlines\"\r\nlast,,\n");
+        assert_eq!(records, [
+            vec!["a", "b", "c"],
+            vec!["x, y", "say \"hi\"", "two\nlines"],
+            vec!["last", "", ""],
+        ]);
+    }
+
+    #[test]
+    fn csv_times_from_a_duration() {
+        let path = TempPath::with("duration.csv", "\
+start,end,minutes,project
+2026-10-01T09:00:00+02:00,,25,thesis
+,2026-10-01T10:00:00+02:00,30,
+2026-10-01T11:00:00+02:00,,,
+");
+        let mut imported = Imported::default();
+        let columns = CsvColumns { duration_column: Some("minutes".into()), project_column: Some("project".into()), ..columns() };
+        read_csv(&path, &columns, &mut imported).unwrap();
+
+        let times: Vec<_> = imported.sessions.iter()
+            .map(|s| (at(s.started.as_deref().unwrap()), at(&s.timestamp)))
+            .collect();
+        assert_eq!(times, [
+            (at("2026-10-01T09:00:00+02:00"), at("2026-10-01T09:25:00+02:00")),
+            (at("2026-10-01T09:30:00+02:00"), at("2026-10-01T10:00:00+02:00")),
+        ]);
+        assert_eq!(imported.sessions[0].project.as_deref(), Some("thesis"));
+        assert_eq!(imported.unreadable, 1);
+    }
+
+    #[test]
+    fn sessions_within_a_minute_are_the_same() {
+        let work = session(at("2026-10-01T09:00:00+02:00"), at("2026-10-01T09:25:00+02:00"), "Work", None, Vec::new());
+        let shifted = |secs| {
+            let d = chrono::Duration::seconds(secs);
+            session(at("2026-10-01T09:00:00+02:00") + d, at("2026-10-01T09:25:00+02:00") + d, "Work", None, Vec::new())
+        };
+        assert!(same_session(&work, &shifted(45)));
+        assert!(!same_session(&work, &shifted(90)));
+
+        let rest = session(at("2026-10-01T09:00:00+02:00"), at("2026-10-01T09:25:00+02:00"), "Short Break", None, Vec::new());
+        assert!(!same_session(&work, &rest));
+    }
+
+    #[test]
+    fn timewarrior_intervals() {
+        let path = TempPath::with("timew.data", r#"inc 20261001T070000Z - 20261001T072500Z # thesis "deep \"focus\" time"
+inc 20261001T073000Z - 20261001T075500Z # review "a # b" # "read \"chapter 2\" # twice"
+inc 20261001T080000Z - 20261001T082500Z # # "no tags, just a note"
+inc 20261001T090000Z # open
+inc 20261001T nonsense
+"#);
+        let mut imported = Imported::default();
+        read_timewarrior(&path, &mut imported).unwrap();
+
+        assert_eq!(imported.sessions.len(), 3);
+        let s = &imported.sessions[0];
+        assert_eq!(at(s.started.as_deref().unwrap()), at("2026-10-01T07:00:00Z"));
+        assert_eq!(s.elapsed_secs, 25 * 60);
+        assert_eq!(s.tags, ["thesis", "deep-\"focus\"-time", IMPORTED_TAG]);
+        // Annotations after the second `#` are left out.
+        assert_eq!(imported.sessions[1].tags, ["review", "a-#-b", IMPORTED_TAG]);
+        assert_eq!(imported.sessions[2].tags, [IMPORTED_TAG]);
+        assert_eq!(imported.unreadable, 1);
+    }
+}
@@ -1,5 +1,21 @@
-use clap::Parser;
-use chrono::Datelike;
+mod bar;
+mod commands;
+mod daemon;
+mod export;
+mod ics;
+mod import;
+mod report;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod storage;
+#[cfg(test)]
+mod testing;
+mod timewarrior;
+
+use clap::{Parser, Subcommand};
+use chrono::{Datelike, Timelike};
+use daemon::{Remote, Request, SuspendChoice};
+use storage::{get_path, load_json, save_json, Storage, Versioned};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
@@ -8,7 +24,7 @@ use crossterm::{
 use notify_rust::{Notification, Urgency};
 use ratatui::{prelude::*, widgets::*};
 use serde::{Deserialize, Serialize};
-use std::{fs, io, path::PathBuf, time::{Duration, Instant}};
+use std::{collections::BTreeMap, io, path::PathBuf, time::{Duration, Instant, SystemTime}};
 
 // ============================================================================
 // Type Aliases & Constants
@@ -17,8 +33,16 @@ use std::{fs, io, path::PathBuf, time::{Duration, Instant}};
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 const TICK_RATE: Duration = Duration::from_millis(50);
 const AUTO_SAVE_INTERVAL: Duration = Duration::from_secs(5);
-const MAX_HISTORY: usize = 100;
+const SUSPEND_THRESHOLD: Duration = Duration::from_secs(30);
+const STATUS_MESSAGE_TIME: Duration = Duration::from_secs(6);
 const DAILY_FMT: &str = "%Y-%m-%d";
+/// Periods the rhythm view can look back over, in days; `None` is all time.
+const RHYTHM_RANGES: &[(Option<i64>, &str)] = &[
+    (Some(30), "last 30 days"),
+    (Some(90), "last 90 days"),
+    (Some(365), "last year"),
+    (None, "all time"),
+];
 
 // ============================================================================
 // CLI Arguments
@@ -27,20 +51,136 @@ const DAILY_FMT: &str = "%Y-%m-%d";
 #[derive(Parser, Clone)]
 #[command(author, version, about = "🍅 rtimer - A Beautiful Terminal Pomodoro Timer")]
 struct Args {
-    #[arg(short, long, value_parser = parse_duration)]
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[arg(short, long, global = true, value_parser = parse_duration)]
     work: Option<f64>,
-    #[arg(short, long, value_parser = parse_duration)]
+    #[arg(short, long, global = true, value_parser = parse_duration)]
     rest: Option<f64>,
-    #[arg(short, long, value_parser = parse_duration)]
+    #[arg(short, long, global = true, value_parser = parse_duration)]
     long_break: Option<f64>,
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     sessions: Option<u32>,
-    #[arg(short = 't', long)]
+    #[arg(short = 't', long, global = true)]
     theme: Option<String>,
-    #[arg(long)]
+    #[arg(long, global = true)]
     no_sound: bool,
-    #[arg(long)]
+    #[arg(long, global = true, value_enum)]
+    on_suspend: Option<SuspendPolicy>,
+    #[arg(long, global = true)]
     resume: bool,
+    /// Keep config, stats and timer state in DIR instead of the XDG directories
+    #[arg(long, global = true, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
+    /// Open the timer without saving anything, even if another instance holds the data directory
+    #[arg(long)]
+    read_only: bool,
+}
+
+#[derive(Subcommand, Clone)]
+enum Command {
+    /// Run the timer in the background and serve clients over a Unix socket
+    Daemon,
+    /// Show the current phase and remaining time
+    Status {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Start a fresh focus session (honours --work/--rest/--long-break)
+    Start {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Pause the timer
+    Pause {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Resume the timer
+    Resume {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Skip to the next phase
+    Skip {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Restart the current phase
+    Reset {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show totals computed from the session history
+    Stats {
+        /// Show the totals recomputed from the recorded sessions alone, without those carried over from older versions
+        #[arg(long)]
+        rebuild: bool,
+        /// With --rebuild, drop the carried-over totals for good (they are backed up first)
+        #[arg(long, requires = "rebuild")]
+        force: bool,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Switch where statistics and notes are stored, copying them over
+    Storage {
+        #[arg(value_enum)]
+        backend: StorageBackend,
+        /// Import from this stats.json instead of the current backend
+        #[arg(long, value_name = "PATH")]
+        from: Option<PathBuf>,
+    },
+    /// Summarize focus time, sessions and notes over a range of days
+    Report {
+        /// First day to include (YYYY-MM-DD); defaults to six days before --to
+        #[arg(long, value_name = "DATE")]
+        from: Option<chrono::NaiveDate>,
+        /// Last day to include (YYYY-MM-DD); defaults to today
+        #[arg(long, value_name = "DATE")]
+        to: Option<chrono::NaiveDate>,
+        #[arg(short, long, value_enum, default_value = "md")]
+        format: report::ReportFormat,
+    },
+    /// Export sessions, notes, tasks or label totals as CSV or JSON Lines, or sessions as iCalendar
+    Export {
+        #[arg(value_enum, default_value = "sessions")]
+        table: export::ExportTable,
+        #[arg(short, long, value_enum, default_value = "csv")]
+        format: export::ExportFormat,
+        /// Write here instead of to stdout
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+        /// Only sessions and notes from this day on (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        from: Option<chrono::NaiveDate>,
+        /// Only sessions and notes up to this day (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        to: Option<chrono::NaiveDate>,
+    },
+    /// Merge history from Timewarrior, a CSV file or another rtimer into the session log
+    Import {
+        #[arg(value_enum)]
+        source: import::ImportSource,
+        /// Files to read; Timewarrior defaults to its whole database
+        #[arg(value_name = "PATH")]
+        paths: Vec<PathBuf>,
+        #[command(flatten)]
+        columns: import::CsvColumns,
+        /// Only import sessions with this tag; repeat to require several
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+        /// Report what would be imported without saving anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print the timer for a status bar, one line per change
+    Bar {
+        #[arg(short, long, value_enum, default_value = "waybar")]
+        format: bar::BarFormat,
+        /// Print the current state once and exit (for tmux's #() and polling bars)
+        #[arg(long)]
+        once: bool,
+    },
 }
 
 fn parse_duration(s: &str) -> std::result::Result<f64, String> {
@@ -66,7 +206,9 @@ fn parse_duration(s: &str) -> std::result::Result<f64, String> {
 // ============================================================================
 
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 struct Config {
+    schema_version: u32,
     work_duration: f64,
     rest_duration: f64,
     long_break_duration: f64,
@@ -75,11 +217,30 @@ struct Config {
     theme: String,
     auto_start_next: bool,
     extended_break_reminder_hours: f64,
+    suspend_policy: SuspendPolicy,
+    storage: StorageBackend,
+    /// Completed work sessions to aim for each day; 0 turns the goal off.
+    daily_goal_sessions: u32,
+    /// Focus hours to aim for each ISO week; 0 turns the goal off.
+    weekly_goal_hours: f64,
+    /// Completed work sessions a day needs to keep the streak going.
+    streak_min_sessions: u32,
+    /// Hour of the day (0-23) from which rtimer warns that today would
+    /// break the streak; unset means no warning.
+    streak_reminder_hour: Option<u32>,
+    /// An .ics file rewritten with all completed sessions whenever a phase
+    /// ends, for calendar apps to subscribe to.
+    calendar_file: Option<PathBuf>,
+    /// Mirror focus sessions into Timewarrior: "off", "data" or "command".
+    timewarrior: timewarrior::Mode,
+    /// Log what would be sent to Timewarrior to timewarrior.log instead.
+    timewarrior_dry_run: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            schema_version: Config::VERSION,
             work_duration: 25.0,
             rest_duration: 5.0,
             long_break_duration: 15.0,
@@ -88,52 +249,518 @@ impl Default for Config {
             theme: "default".into(),
             auto_start_next: true,
             extended_break_reminder_hours: 2.0,
+            suspend_policy: SuspendPolicy::default(),
+            storage: StorageBackend::default(),
+            daily_goal_sessions: 0,
+            weekly_goal_hours: 0.0,
+            streak_min_sessions: 1,
+            streak_reminder_hour: None,
+            calendar_file: None,
+            timewarrior: timewarrior::Mode::Off,
+            timewarrior_dry_run: false,
+        }
+    }
+}
+
+/// Where statistics, session history and notes are kept.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+enum StorageBackend {
+    /// stats.json, rewritten on every save
+    #[default]
+    Json,
+    /// stats.db, appending sessions as they finish
+    Sqlite,
+}
+
+impl StorageBackend {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Sqlite => "SQLite",
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Json => "stats.json",
+            Self::Sqlite => "stats.db",
+        }
+    }
+}
+
+/// What to do with time that passed while the machine was asleep.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+enum SuspendPolicy {
+    /// Count the sleep as elapsed, finishing phases that ran out meanwhile
+    #[value(name = "count")]
+    CountElapsed,
+    /// Pause the timer at the moment of suspend
+    Pause,
+    /// Pause and ask what to do on the next redraw
+    #[default]
+    Ask,
+}
+
+impl SuspendPolicy {
+    fn label(self) -> &'static str {
+        match self {
+            Self::CountElapsed => "Count as elapsed",
+            Self::Pause => "Pause",
+            Self::Ask => "Ask",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::CountElapsed => Self::Pause,
+            Self::Pause => Self::Ask,
+            Self::Ask => Self::CountElapsed,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            Self::CountElapsed => Self::Ask,
+            Self::Pause => Self::CountElapsed,
+            Self::Ask => Self::Pause,
         }
     }
 }
 
+/// Something to work on, with how many pomodoros it was expected to take.
+#[derive(Serialize, Deserialize, Clone)]
+struct Task {
+    id: u64,
+    name: String,
+    estimate: u32,
+    done: bool,
+    created: String,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 struct Note {
     timestamp: String,
     content: String,
     phase: String,
+    /// The project being worked on when the note was written.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    /// `#tags` from the note text.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+/// Collects `#tags` from free text, lowercased and without duplicates.
+fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    for word in text.split_whitespace() {
+        let Some(tag) = word.strip_prefix('#') else { continue };
+        let tag: String = tag
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '/'))
+            .flat_map(char::to_lowercase)
+            .collect();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+/// Splits input like `rtimer #rust #review` into a project and tags.
+fn parse_label(input: &str) -> (Option<String>, Vec<String>) {
+    let project = input
+        .split_whitespace()
+        .filter(|w| !w.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join(" ");
+    ((!project.is_empty()).then_some(project), parse_tags(input))
+}
+
+/// Splits input like `Write report ~3` into a task name and its estimate
+/// in pomodoros, which is 1 if none is given.
+fn parse_task(input: &str) -> (String, u32) {
+    let input = input.trim();
+    if let Some((name, estimate)) = input.rsplit_once('~')
+        && name.ends_with(char::is_whitespace)
+        && let Ok(estimate) = estimate.parse::<u32>()
+    {
+        return (name.trim_end().to_string(), estimate.max(1));
+    }
+    (input.to_string(), 1)
+}
+
+fn format_label(project: Option<&str>, tags: &[String]) -> String {
+    let mut parts: Vec<String> = project.map(String::from).into_iter().collect();
+    parts.extend(tags.iter().map(|t| format!("#{}", t)));
+    parts.join(" ")
+}
+
+/// Why a phase ended.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum EndReason {
+    /// Ran for its full length.
+    Completed,
+    Skipped,
+    Reset,
+    /// rtimer exited mid-phase. If the phase is resumed later, the rest of
+    /// it is logged as a record of its own.
+    Quit,
+    /// Ran out while the machine was asleep.
+    Suspended,
+}
+
+impl EndReason {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::Skipped => "skipped",
+            Self::Reset => "reset",
+            Self::Quit => "quit",
+            Self::Suspended => "suspended",
+        }
+    }
+}
+
+/// Who caused an interruption, per the Pomodoro Technique.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+enum InterruptionKind {
+    /// Your own distraction
+    Internal,
+    /// Someone or something else
+    External,
+}
+
+impl InterruptionKind {
+    fn label(kind: Option<Self>) -> &'static str {
+        match kind {
+            None => "unspecified",
+            Some(Self::Internal) => "internal",
+            Some(Self::External) => "external",
+        }
+    }
+
+    fn cycle(kind: Option<Self>) -> Option<Self> {
+        match kind {
+            None => Some(Self::Internal),
+            Some(Self::Internal) => Some(Self::External),
+            Some(Self::External) => None,
+        }
+    }
+}
+
+/// A pause during a work phase.
+#[derive(Serialize, Deserialize, Clone)]
+struct Interruption {
+    /// When the pause started.
+    timestamp: String,
+    duration_secs: u64,
+    kind: Option<InterruptionKind>,
+    #[serde(default)]
+    reason: String,
 }
 
+/// One stretch of a phase, from when it started (or was resumed) until it
+/// ended. Times are RFC 3339 in the local timezone of the time.
 #[derive(Serialize, Deserialize, Clone)]
 struct SessionRecord {
+    /// Not known for records from before start times were kept.
+    started: Option<String>,
+    /// When the phase ended.
     timestamp: String,
     phase_type: String,
+    /// Planned length in minutes.
     duration: u64,
-    completed: bool,
+    /// Time actually spent in the phase, excluding pauses.
+    elapsed_secs: u64,
+    paused_secs: u64,
+    end_reason: EndReason,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    interruptions: Vec<Interruption>,
+    /// The task a work phase was spent on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+impl SessionRecord {
+    fn is_work(&self) -> bool {
+        self.phase_type == "Work"
+    }
+
+    fn completed(&self) -> bool {
+        self.end_reason == EndReason::Completed
+    }
+
+    /// The calendar day the session ended on, in the timezone it was
+    /// recorded in.
+    fn date(&self) -> Option<chrono::NaiveDate> {
+        chrono::DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|t| t.date_naive())
+    }
+
+    /// When the session started, or ended for records from before start
+    /// times were kept.
+    fn start_time(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        chrono::DateTime::parse_from_rfc3339(self.started.as_deref().unwrap_or(&self.timestamp)).ok()
+    }
+}
+
+/// Aggregates over a set of session records.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+#[serde(default)]
+struct Totals {
+    work_sessions: u32,
+    work_secs: u64,
+    break_secs: u64,
+}
+
+impl Totals {
+    fn add(&mut self, s: &SessionRecord) {
+        if s.is_work() {
+            self.work_sessions += s.completed() as u32;
+            self.work_secs += s.elapsed_secs;
+        } else {
+            self.break_secs += s.elapsed_secs;
+        }
+    }
+}
+
+impl std::ops::AddAssign for Totals {
+    fn add_assign(&mut self, other: Self) {
+        self.work_sessions += other.work_sessions;
+        self.work_secs += other.work_secs;
+        self.break_secs += other.break_secs;
+    }
+}
+
+/// Completed versus skipped work sessions in one bucket of the rhythm view.
+#[derive(Clone, Copy, Default)]
+struct Completion {
+    completed: u32,
+    skipped: u32,
 }
 
+impl Completion {
+    fn add(&mut self, s: &SessionRecord) {
+        match s.end_reason {
+            EndReason::Completed => self.completed += 1,
+            EndReason::Skipped => self.skipped += 1,
+            _ => {}
+        }
+    }
+
+    fn rate(&self) -> Option<f64> {
+        let all = self.completed + self.skipped;
+        (all > 0).then(|| self.completed as f64 / all as f64)
+    }
+}
+
+/// The session log plus notes. Every aggregate is computed from
+/// `session_history`; nothing is counted separately.
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 struct Statistics {
-    total_sessions: u32,
-    total_work_time: u64,
-    total_break_time: u64,
-    sessions_today: u32,
-    last_session_date: String,
+    schema_version: u32,
     session_history: Vec<SessionRecord>,
-    weekly_sessions: Vec<u32>,
     notes: Vec<Note>,
+    tasks: Vec<Task>,
+    /// The id the next task gets. Ids are never reused, so sessions logged
+    /// against a deleted task don't show up under a new one. 0 in files from
+    /// older versions.
+    next_task_id: u64,
+    /// The task new work sessions are attributed to.
+    active_task: Option<u64>,
+    /// The project and tags new work sessions and notes are labelled with.
+    active_project: Option<String>,
+    active_tags: Vec<String>,
+    /// Totals carried over from before the full history was kept.
+    archived: Totals,
+    /// The day the streak reminder last fired, so restarts don't repeat it.
+    streak_warned: Option<String>,
 }
 
 impl Default for Statistics {
     fn default() -> Self {
         Self {
-            total_sessions: 0,
-            total_work_time: 0,
-            total_break_time: 0,
-            sessions_today: 0,
-            last_session_date: chrono::Local::now().format(DAILY_FMT).to_string(),
+            schema_version: Statistics::VERSION,
             session_history: Vec::new(),
-            weekly_sessions: vec![0; 7],
             notes: Vec::new(),
+            tasks: Vec::new(),
+            next_task_id: 0,
+            active_task: None,
+            active_project: None,
+            active_tags: Vec::new(),
+            archived: Totals::default(),
+            streak_warned: None,
+        }
+    }
+}
+
+impl Statistics {
+    fn task(&self, id: u64) -> Option<&Task> {
+        self.tasks.iter().find(|t| t.id == id)
+    }
+
+    /// Hands out an id no task, current or deleted, has had.
+    fn new_task_id(&mut self) -> u64 {
+        let used = self.tasks.iter().map(|t| t.id)
+            .chain(self.session_history.iter().filter_map(|s| s.task))
+            .max().unwrap_or(0);
+        let id = self.next_task_id.max(used + 1);
+        self.next_task_id = id + 1;
+        id
+    }
+
+    /// Completed work sessions spent on a task.
+    fn task_pomodoros(&self, id: u64) -> u32 {
+        self.session_history.iter()
+            .filter(|s| s.task == Some(id) && s.is_work() && s.completed())
+            .count() as u32
+    }
+
+    /// Work totals per project, and per tag. Sessions without a project
+    /// are left out of the former.
+    fn label_totals(&self) -> (BTreeMap<&str, Totals>, BTreeMap<&str, Totals>) {
+        let mut projects = BTreeMap::new();
+        let mut tags = BTreeMap::new();
+        for s in self.session_history.iter().filter(|s| s.is_work()) {
+            if let Some(project) = &s.project {
+                projects.entry(project.as_str()).or_insert_with(Totals::default).add(s);
+            }
+            for tag in &s.tags {
+                tags.entry(tag.as_str()).or_insert_with(Totals::default).add(s);
+            }
+        }
+        (projects, tags)
+    }
+
+    fn totals(&self) -> Totals {
+        self.session_history.iter().fold(self.archived, |mut t, s| {
+            t.add(s);
+            t
+        })
+    }
+
+    /// Totals per calendar day from `from` to `to`, inclusive. Records are in
+    /// chronological order, so this only looks back as far as `from`.
+    fn daily_totals(&self, from: chrono::NaiveDate, to: chrono::NaiveDate) -> BTreeMap<chrono::NaiveDate, Totals> {
+        let mut days = BTreeMap::new();
+        for s in self.session_history.iter().rev() {
+            let Some(date) = s.date() else { continue };
+            if date < from {
+                break;
+            }
+            if date <= to {
+                days.entry(date).or_insert_with(Totals::default).add(s);
+            }
+        }
+        days
+    }
+
+    fn day_totals(&self, date: chrono::NaiveDate) -> Totals {
+        self.daily_totals(date, date).remove(&date).unwrap_or_default()
+    }
+
+    fn sessions_today(&self) -> u32 {
+        self.day_totals(chrono::Local::now().date_naive()).work_sessions
+    }
+
+    /// Today's totals and those of the ISO week so far, which is what goals
+    /// are measured against.
+    fn goal_totals(&self, today: chrono::NaiveDate) -> (Totals, Totals) {
+        let days = self.daily_totals(today.week(chrono::Weekday::Mon).first_day(), today);
+        let week = days.values().fold(Totals::default(), |mut week, &day| {
+            week += day;
+            week
+        });
+        (days.get(&today).copied().unwrap_or_default(), week)
+    }
+
+    /// In how many periods a goal was `met`, out of how many, from the
+    /// period of the first recorded session up to the one containing
+    /// `today`. Periods without any sessions count as missed; the current
+    /// one only counts once it is met.
+    fn goal_hits(
+        &self,
+        today: chrono::NaiveDate,
+        period_start: impl Fn(chrono::NaiveDate) -> chrono::NaiveDate,
+        met: impl Fn(&Totals) -> bool,
+    ) -> (usize, usize) {
+        let mut periods: BTreeMap<chrono::NaiveDate, Totals> = BTreeMap::new();
+        for s in &self.session_history {
+            if let Some(date) = s.date() {
+                periods.entry(period_start(date)).or_default().add(s);
+            }
+        }
+        let Some(&first) = periods.keys().next() else { return (0, 0) };
+        let current = period_start(today);
+        let past = first.iter_days().take_while(|&d| d < current).filter(|&d| period_start(d) == d).count();
+        let current_met = periods.get(&current).is_some_and(&met);
+        let hits = periods.iter().filter(|&(&p, t)| p < current && met(t)).count();
+        (hits + current_met as usize, past + current_met as usize)
+    }
+
+    /// The current and the longest run of consecutive days with at least
+    /// `min_sessions` completed work sessions. Today only ends the current
+    /// streak once it is over, so a streak still counts while today is
+    /// short of the minimum.
+    fn streaks(&self, today: chrono::NaiveDate, min_sessions: u32) -> (u32, u32) {
+        let Some(first) = self.session_history.iter().find_map(|s| s.date()) else { return (0, 0) };
+        let days = self.daily_totals(first, today);
+        let met = |date: &chrono::NaiveDate| days.get(date).is_some_and(|t| t.work_sessions >= min_sessions.max(1));
+        let (mut run, mut longest) = (0, 0);
+        for date in first.iter_days().take_while(|d| *d <= today) {
+            run = if met(&date) { run + 1 } else { 0 };
+            longest = longest.max(run);
         }
+        let current = match met(&today) {
+            true => run,
+            false => today.pred_opt().map_or(0, |yesterday| {
+                yesterday.iter_days().rev().take_while(|d| *d >= first && met(d)).count() as u32
+            }),
+        };
+        (current, longest)
+    }
+
+    /// Work sessions since `from` (or ever) by the hour of day and the
+    /// weekday they started on.
+    fn rhythm(&self, from: Option<chrono::NaiveDate>) -> ([Completion; 24], [Completion; 7]) {
+        let mut hours = [Completion::default(); 24];
+        let mut weekdays = [Completion::default(); 7];
+        for s in self.session_history.iter().rev().filter(|s| s.is_work()) {
+            let Some(start) = s.start_time() else { continue };
+            if from.is_some_and(|from| start.date_naive() < from) {
+                break;
+            }
+            hours[start.hour() as usize].add(s);
+            weekdays[start.weekday().num_days_from_monday() as usize].add(s);
+        }
+        (hours, weekdays)
+    }
+
+    /// Totals for each day of the ISO week starting on `monday`.
+    fn week_totals(&self, monday: chrono::NaiveDate) -> [Totals; 7] {
+        let days = self.daily_totals(monday, monday + chrono::Duration::days(6));
+        std::array::from_fn(|i| {
+            days.get(&(monday + chrono::Duration::days(i as i64))).copied().unwrap_or_default()
+        })
     }
 }
 
+/// Monday of the ISO week `weeks_back` weeks before the one containing `today`.
+fn week_start(today: chrono::NaiveDate, weeks_back: u32) -> chrono::NaiveDate {
+    today.week(chrono::Weekday::Mon).first_day() - chrono::Duration::weeks(weeks_back as i64)
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Copy)]
 enum Phase {
     Work,
@@ -150,7 +777,7 @@ impl Phase {
         }
     }
     
-    fn to_str(&self) -> &str {
+    fn to_str(self) -> &'static str {
         match self {
             Self::Work => "work",
             Self::ShortBreak => "short_break",
@@ -167,12 +794,126 @@ impl Phase {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
 struct TimerState {
+    schema_version: u32,
     time_remaining_secs: u64,
     phase: String,
     session_count: u32,
     paused: bool,
+    /// Focused time of this phase already logged by a `Quit` record.
+    logged_secs: u64,
+    /// Phase lengths in minutes, so lengths given to `rtimer start` last
+    /// for the rest of the cycle. Missing in files from older versions.
+    work_duration: Option<f64>,
+    rest_duration: Option<f64>,
+    long_break_duration: Option<f64>,
+    /// Set when nothing is left running the timer, like after `rtimer start`
+    /// without a daemon; the phase then keeps counting in wall-clock time.
+    clock: Option<SavedClock>,
+    /// Pauses of the current work phase not yet logged with a session.
+    interruptions: Vec<Interruption>,
+}
+
+/// A `PhaseClock` as saved in timer_state.json, with RFC 3339 times.
+#[derive(Serialize, Deserialize)]
+struct SavedClock {
+    since: String,
+    started: String,
+    paused_secs: f64,
+    paused_since: Option<String>,
+}
+
+/// Wall-clock accounting for the current phase. Remaining time is derived
+/// from when the phase started minus the time spent paused, so a slow frame
+/// or a blocked draw never makes the countdown drift.
+#[derive(Clone, Copy)]
+struct PhaseClock {
+    /// When the clock was started. `started` is shifted back from this by
+    /// any elapsed time carried over.
+    since: SystemTime,
+    started: SystemTime,
+    paused_total: Duration,
+    paused_since: Option<SystemTime>,
+}
+
+impl PhaseClock {
+    fn start(now: SystemTime, already_elapsed: Duration, paused: bool) -> Self {
+        Self {
+            since: now,
+            started: now.checked_sub(already_elapsed).unwrap_or(now),
+            paused_total: Duration::ZERO,
+            paused_since: paused.then_some(now),
+        }
+    }
+
+    fn elapsed(&self, now: SystemTime) -> Duration {
+        let until = self.paused_since.unwrap_or(now);
+        until.duration_since(self.started)
+            .unwrap_or_default()
+            .saturating_sub(self.paused_total)
+    }
+
+    /// The moment the phase runs out, if it is currently running.
+    fn end_time(&self, total: Duration) -> Option<SystemTime> {
+        match self.paused_since {
+            Some(_) => None,
+            None => Some(self.started + self.paused_total + total),
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused_since.is_some()
+    }
+
+    fn paused(&self, now: SystemTime) -> Duration {
+        let current = self.paused_since
+            .map(|since| now.duration_since(since).unwrap_or_default())
+            .unwrap_or_default();
+        self.paused_total + current
+    }
+
+    fn pause(&mut self, now: SystemTime) {
+        if self.paused_since.is_none() {
+            self.paused_since = Some(now);
+        }
+    }
+
+    fn resume(&mut self, now: SystemTime) {
+        if let Some(since) = self.paused_since.take() {
+            self.paused_total += now.duration_since(since).unwrap_or_default();
+        }
+    }
+
+    /// Drops the current pause as if it never happened, so the paused
+    /// stretch counts towards the phase.
+    fn discard_pause(&mut self) {
+        self.paused_since = None;
+    }
+
+    fn save(&self) -> SavedClock {
+        let time = |t: SystemTime| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339();
+        SavedClock {
+            since: time(self.since),
+            started: time(self.started),
+            paused_secs: self.paused_total.as_secs_f64(),
+            paused_since: self.paused_since.map(time),
+        }
+    }
+
+    fn restore(saved: &SavedClock) -> Option<Self> {
+        let time = |t: &str| chrono::DateTime::parse_from_rfc3339(t).ok().map(SystemTime::from);
+        Some(Self {
+            since: time(&saved.since)?,
+            started: time(&saved.started)?,
+            paused_total: Duration::try_from_secs_f64(saved.paused_secs).ok()?,
+            paused_since: match &saved.paused_since {
+                Some(t) => Some(time(t)?),
+                None => None,
+            },
+        })
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -191,8 +932,13 @@ enum View {
     StatsSummary,
     StatsDetailed,
     StatsHistory,
+    StatsInterruptions,
+    StatsProjects,
+    StatsHeatmap,
+    StatsRhythm,
     Settings,
     Notes,
+    Tasks,
 }
 
 #[derive(PartialEq, Clone, Copy)]
@@ -201,10 +947,15 @@ enum SettingsField {
     RestDuration,
     LongBreakDuration,
     SessionsBeforeLongBreak,
+    DailyGoal,
+    WeeklyGoal,
+    StreakMinSessions,
+    StreakReminder,
     Theme,
     SoundEnabled,
     AutoStartNext,
     ExtendedBreakReminder,
+    SuspendPolicy,
 }
 
 impl SettingsField {
@@ -213,28 +964,53 @@ impl SettingsField {
             Self::WorkDuration => Self::RestDuration,
             Self::RestDuration => Self::LongBreakDuration,
             Self::LongBreakDuration => Self::SessionsBeforeLongBreak,
-            Self::SessionsBeforeLongBreak => Self::Theme,
+            Self::SessionsBeforeLongBreak => Self::DailyGoal,
+            Self::DailyGoal => Self::WeeklyGoal,
+            Self::WeeklyGoal => Self::StreakMinSessions,
+            Self::StreakMinSessions => Self::StreakReminder,
+            Self::StreakReminder => Self::Theme,
             Self::Theme => Self::SoundEnabled,
             Self::SoundEnabled => Self::AutoStartNext,
             Self::AutoStartNext => Self::ExtendedBreakReminder,
-            Self::ExtendedBreakReminder => Self::WorkDuration,
+            Self::ExtendedBreakReminder => Self::SuspendPolicy,
+            Self::SuspendPolicy => Self::WorkDuration,
         }
     }
     
     fn prev(self) -> Self {
         match self {
-            Self::WorkDuration => Self::ExtendedBreakReminder,
+            Self::WorkDuration => Self::SuspendPolicy,
             Self::RestDuration => Self::WorkDuration,
             Self::LongBreakDuration => Self::RestDuration,
             Self::SessionsBeforeLongBreak => Self::LongBreakDuration,
-            Self::Theme => Self::SessionsBeforeLongBreak,
+            Self::DailyGoal => Self::SessionsBeforeLongBreak,
+            Self::WeeklyGoal => Self::DailyGoal,
+            Self::StreakMinSessions => Self::WeeklyGoal,
+            Self::StreakReminder => Self::StreakMinSessions,
+            Self::Theme => Self::StreakReminder,
             Self::SoundEnabled => Self::Theme,
             Self::AutoStartNext => Self::SoundEnabled,
             Self::ExtendedBreakReminder => Self::AutoStartNext,
+            Self::SuspendPolicy => Self::ExtendedBreakReminder,
         }
     }
 }
 
+/// The quick "why did you stop?" prompt shown after pausing a work phase.
+#[derive(Default)]
+struct InterruptionPrompt {
+    kind: Option<InterruptionKind>,
+    reason: String,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum TasksMode {
+    Viewing,
+    Adding,
+    Editing,
+    ConfirmingDelete,
+}
+
 #[derive(PartialEq, Clone, Copy)]
 enum NotesMode {
     Viewing,
@@ -248,8 +1024,12 @@ enum NotesMode {
 // ============================================================================
 
 struct AppState {
-    time_remaining: Duration,
-    paused: bool,
+    clock: PhaseClock,
+    /// Focused time of the current phase already in the session log.
+    logged_elapsed: Duration,
+    /// Pauses during the current phase, logged with it when it ends.
+    interruptions: Vec<Interruption>,
+    interrupted_since: Option<SystemTime>,
     session_count: u32,
     phase: Phase,
     work_duration: Duration,
@@ -269,12 +1049,43 @@ struct AppState {
     notes_mode: NotesMode,
     notes_input: String,
     selected_note_index: Option<usize>,
+    tasks_mode: TasksMode,
+    tasks_input: String,
+    selected_task_index: Option<usize>,
+    interruption_prompt: Option<InterruptionPrompt>,
+    /// Project and tags being typed, as in `rtimer #rust #review`.
+    label_input: Option<String>,
+    /// Which week the weekly stats view shows, counting back from this one.
+    stats_weeks_back: u32,
+    /// The day picked in the heatmap view.
+    heatmap_day: chrono::NaiveDate,
+    /// Index into `RHYTHM_RANGES` for the rhythm view.
+    rhythm_range: usize,
+    /// Feedback like where an export went, shown for `STATUS_MESSAGE_TIME`.
+    status_message: Option<(String, Instant)>,
     needs_save: bool,
     last_save: Instant,
     auto_start_next: bool,
     extended_break_hours: f64,
+    daily_goal: u32,
+    weekly_goal_hours: f64,
+    streak_min_sessions: u32,
+    streak_reminder_hour: Option<u32>,
+    calendar_file: Option<PathBuf>,
+    timewarrior: timewarrior::Mirror,
     last_break_check: Instant,
     work_time_since_break: Duration,
+    suspend_policy: SuspendPolicy,
+    last_update: SystemTime,
+    pending_suspend: Option<Duration>,
+    /// Set when time slept through is being counted, so phases that ran
+    /// out meanwhile are logged as `Suspended` rather than completed.
+    suspended: bool,
+    remote: Option<Remote>,
+    revision: u64,
+    read_only: bool,
+    store: Option<Box<dyn Storage>>,
+    storage_backend: StorageBackend,
 }
 
 impl AppState {
@@ -284,26 +1095,40 @@ impl AppState {
         let rest = Duration::from_secs_f64(config.rest_duration * 60.0);
         let long = Duration::from_secs_f64(config.long_break_duration * 60.0);
         
-        let (time_remaining, paused, session_count, phase) = if let Some(saved) = saved_state {
-            (
-                Duration::from_secs(saved.time_remaining_secs),
-                saved.paused,
-                saved.session_count,
-                Phase::from_str(&saved.phase),
-            )
+        let now = SystemTime::now();
+        let (clock, session_count, phase, logged_secs, interruptions) = if let Some(saved) = saved_state {
+            let phase = Phase::from_str(&saved.phase);
+            let total = match phase {
+                Phase::Work => work,
+                Phase::ShortBreak => rest,
+                Phase::LongBreak => long,
+            };
+            let clock = saved.clock.as_ref().and_then(PhaseClock::restore).unwrap_or_else(|| {
+                let elapsed = total.saturating_sub(Duration::from_secs(saved.time_remaining_secs));
+                PhaseClock::start(now, elapsed, saved.paused)
+            });
+            (clock, saved.session_count, phase, saved.logged_secs, saved.interruptions)
         } else {
-            (work, false, 1, Phase::Work)
+            (PhaseClock::start(now, Duration::ZERO, false), 1, Phase::Work, 0, Vec::new())
         };
+        // A work phase paused without a daemon is still in its last
+        // interruption, which ends when the clock resumes.
+        let interrupted_since = clock.paused_since.filter(|_| phase == Phase::Work && !interruptions.is_empty());
         
         let selected_note_index = if !stats.notes.is_empty() {
             Some(stats.notes.len() - 1)
         } else {
             None
         };
+        let selected_task_index = stats.active_task
+            .and_then(|id| stats.tasks.iter().position(|t| t.id == id))
+            .or(if stats.tasks.is_empty() { None } else { Some(0) });
         
         Self {
-            time_remaining,
-            paused,
+            clock,
+            logged_elapsed: Duration::from_secs(logged_secs),
+            interruptions,
+            interrupted_since,
             session_count,
             phase,
             work_duration: work,
@@ -323,12 +1148,36 @@ impl AppState {
             notes_mode: NotesMode::Viewing,
             notes_input: String::new(),
             selected_note_index,
+            tasks_mode: TasksMode::Viewing,
+            tasks_input: String::new(),
+            selected_task_index,
+            interruption_prompt: None,
+            label_input: None,
+            stats_weeks_back: 0,
+            heatmap_day: chrono::Local::now().date_naive(),
+            rhythm_range: 1,
+            status_message: None,
             needs_save: false,
             last_save: Instant::now(),
             auto_start_next: config.auto_start_next,
             extended_break_hours: config.extended_break_reminder_hours,
+            daily_goal: config.daily_goal_sessions,
+            weekly_goal_hours: config.weekly_goal_hours,
+            streak_min_sessions: config.streak_min_sessions,
+            streak_reminder_hour: config.streak_reminder_hour,
+            calendar_file: config.calendar_file.clone(),
+            timewarrior: timewarrior::Mirror::new(config.timewarrior, config.timewarrior_dry_run),
             last_break_check: Instant::now(),
             work_time_since_break: Duration::ZERO,
+            suspend_policy: config.suspend_policy,
+            last_update: now,
+            pending_suspend: None,
+            suspended: false,
+            remote: None,
+            revision: 0,
+            read_only: false,
+            store: None,
+            storage_backend: config.storage,
         }
     }
     
@@ -348,72 +1197,239 @@ impl AppState {
         }
     }
     
+    fn time_remaining(&self) -> Duration {
+        self.total_duration().saturating_sub(self.clock.elapsed(SystemTime::now()))
+    }
+    
+    fn paused(&self) -> bool {
+        self.clock.is_paused()
+    }
+    
+    fn daily_goal_met(&self, today: &Totals) -> bool {
+        self.daily_goal > 0 && today.work_sessions >= self.daily_goal
+    }
+    
+    fn weekly_goal_met(&self, week: &Totals) -> bool {
+        self.weekly_goal_hours > 0.0 && week.work_secs as f64 >= self.weekly_goal_hours * 3600.0
+    }
+    
+    /// Notifies about goals that were not met at `before` but are now.
+    fn announce_goals(&self, before: (Totals, Totals)) {
+        let (today, week) = self.stats.goal_totals(chrono::Local::now().date_naive());
+        if !self.daily_goal_met(&before.0) && self.daily_goal_met(&today) {
+            notify(
+                "Daily Goal Reached! 🏆",
+                &format!("{} sessions today. Anything more is a bonus.", today.work_sessions),
+                self.sound_enabled,
+            );
+        }
+        if !self.weekly_goal_met(&before.1) && self.weekly_goal_met(&week) {
+            notify(
+                "Weekly Goal Reached! 🏆",
+                &format!("{:.1} focus hours this week.", week.work_secs as f64 / 3600.0),
+                self.sound_enabled,
+            );
+        }
+    }
+    
+    fn selected_task(&self) -> Option<&Task> {
+        self.selected_task_index.and_then(|idx| self.stats.tasks.get(idx))
+    }
+    
+    fn toggle_pause(&mut self) {
+        let now = SystemTime::now();
+        if self.clock.is_paused() {
+            self.resume(now);
+        } else {
+            self.pause(now);
+        }
+    }
+    
+    /// Pauses the timer, logging an interruption if it was a work phase.
+    fn pause(&mut self, now: SystemTime) {
+        if self.clock.is_paused() {
+            return;
+        }
+        self.clock.pause(now);
+        if self.phase == Phase::Work {
+            self.interrupted_since = Some(now);
+            self.interruptions.push(Interruption {
+                timestamp: chrono::DateTime::<chrono::Local>::from(now).to_rfc3339(),
+                duration_secs: 0,
+                kind: None,
+                reason: String::new(),
+            });
+        }
+    }
+    
+    fn resume(&mut self, now: SystemTime) {
+        self.clock.resume(now);
+        self.end_interruption(now);
+    }
+    
+    fn end_interruption(&mut self, now: SystemTime) {
+        if let Some(since) = self.interrupted_since.take()
+            && let Some(interruption) = self.interruptions.last_mut()
+        {
+            interruption.duration_secs = now.duration_since(since).unwrap_or_default().as_secs();
+        }
+    }
+    
+    fn reset_timer(&mut self) {
+        let now = SystemTime::now();
+        self.record_session(now, EndReason::Reset);
+        self.clock = PhaseClock::start(now, Duration::ZERO, false);
+        self.logged_elapsed = Duration::ZERO;
+    }
+    
     fn progress_ratio(&self) -> f64 {
         let total = self.total_duration().as_secs_f64();
-        let remaining = self.time_remaining.as_secs_f64();
+        let remaining = self.time_remaining().as_secs_f64();
         (1.0 - (remaining / total)).clamp(0.0, 1.0)
     }
 
     fn next_phase(&mut self) {
-        self.record_session();
+        self.advance_phase(SystemTime::now(), EndReason::Skipped);
+    }
+    
+    /// Ends the current phase at `at` and starts the next one from that
+    /// same instant, so time between the phase running out and the next
+    /// tick is not lost.
+    fn advance_phase(&mut self, at: SystemTime, reason: EndReason) {
+        // Phases that ran out a while ago, say while the daemon was down,
+        // are not announced one by one.
+        let announce = SystemTime::now().duration_since(at).unwrap_or_default() < SUSPEND_THRESHOLD;
+        let reason = match reason {
+            EndReason::Completed if self.suspended => EndReason::Suspended,
+            reason => reason,
+        };
+        let goals_before = (announce && self.phase == Phase::Work)
+            .then(|| self.stats.goal_totals(chrono::Local::now().date_naive()));
+        let was_work = self.phase == Phase::Work;
+        self.record_session(at, reason);
+        self.revision += 1;
+        if let Some(before) = goals_before {
+            self.announce_goals(before);
+        }
         
         match self.phase {
             Phase::Work => {
-                self.stats.total_work_time += self.work_duration.as_secs() / 60;
-                self.stats.total_sessions += 1;
-                self.stats.sessions_today += 1;
-                self.update_weekly_stats();
                 self.session_count += 1;
-                self.work_time_since_break += self.work_duration;
+                if let Some(s) = self.stats.session_history.last() {
+                    self.work_time_since_break += Duration::from_secs(s.elapsed_secs);
+                }
                 
-                if self.session_count % self.sessions_before_long_break == 0 {
+                if self.session_count.is_multiple_of(self.sessions_before_long_break) {
                     self.phase = Phase::LongBreak;
-                    self.time_remaining = self.long_break_duration;
-                    notify("Long Break Time! 🌴", "Great work! Take a longer break.", self.sound_enabled);
+                    if announce {
+                        notify("Long Break Time! 🌴", "Great work! Take a longer break.", self.sound_enabled);
+                    }
                     self.work_time_since_break = Duration::ZERO;
                 } else {
                     self.phase = Phase::ShortBreak;
-                    self.time_remaining = self.rest_duration;
-                    notify("Break Time! ☕", "Time for a short break.", self.sound_enabled);
+                    if announce {
+                        notify("Break Time! ☕", "Time for a short break.", self.sound_enabled);
+                    }
                 }
             }
             Phase::ShortBreak | Phase::LongBreak => {
-                self.stats.total_break_time += self.total_duration().as_secs() / 60;
                 self.phase = Phase::Work;
-                self.time_remaining = self.work_duration;
-                notify("Back to Work! 🎯", "Let's focus on your next session.", self.sound_enabled);
+                if announce {
+                    notify("Back to Work! 🎯", "Let's focus on your next session.", self.sound_enabled);
+                }
             }
         }
         
-        self.paused = !self.auto_start_next;
+        self.clock = PhaseClock::start(at, Duration::ZERO, !self.auto_start_next);
         self.needs_save = true;
+        self.sync_timewarrior(at, was_work);
+        self.refresh_calendar();
     }
     
-    fn record_session(&mut self) {
-        let now = chrono::Local::now();
-        let completed = self.time_remaining.as_secs() < 5;
+    /// Mirrors the work phase that just ended, and the one starting, into
+    /// Timewarrior if that is configured.
+    fn sync_timewarrior(&mut self, at: SystemTime, work_ended: bool) {
+        if self.timewarrior.mode == timewarrior::Mode::Off || self.read_only {
+            return;
+        }
+        let mut result = Ok(());
+        if work_ended && let Some(s) = self.stats.session_history.last().filter(|s| s.is_work()) {
+            let tags = timewarrior::tags(&self.stats, s.task, s.project.as_deref(), &s.tags);
+            result = self.timewarrior.end(s, &tags);
+        }
+        if self.phase == Phase::Work && !self.clock.is_paused() {
+            let stats = &self.stats;
+            let tags = timewarrior::tags(stats, stats.active_task, stats.active_project.as_deref(), &stats.active_tags);
+            result = result.and(self.timewarrior.start(at.into(), &tags));
+        }
+        if let Err(e) = result {
+            self.status_message = Some((format!("⚠ Timewarrior: {}", e), Instant::now()));
+        }
+    }
+    
+    /// Rewrites the subscribed calendar file, if one is configured.
+    fn refresh_calendar(&mut self) {
+        let Some(path) = &self.calendar_file else { return };
+        if self.read_only {
+            return;
+        }
+        if let Err(e) = storage::write_atomic(path, ics::calendar(&self.stats).as_bytes()) {
+            self.status_message = Some((format!("⚠ Could not update {}: {}", path.display(), e), Instant::now()));
+        }
+    }
+    
+    /// Logs the current phase as having ended at `at`. Phases stopped with
+    /// nothing left to log (a reset right after starting, say) are dropped,
+    /// but every skip is kept.
+    fn record_session(&mut self, at: SystemTime, reason: EndReason) {
+        let total = self.total_duration();
+        let elapsed = self.clock.elapsed(at).min(total);
+        let focused = elapsed.saturating_sub(self.logged_elapsed);
+        self.end_interruption(at);
+        let interruptions = std::mem::take(&mut self.interruptions);
+        let reason = match reason {
+            EndReason::Skipped if (total - elapsed).as_secs() < 5 => EndReason::Completed,
+            reason => reason,
+        };
+        if matches!(reason, EndReason::Reset | EndReason::Quit) && focused.as_secs() == 0 {
+            return;
+        }
         
+        let local = |t: SystemTime| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339();
         self.stats.session_history.push(SessionRecord {
-            timestamp: now.to_rfc3339(),
+            started: Some(local(self.clock.since)),
+            timestamp: local(at),
             phase_type: match self.phase {
                 Phase::Work => "Work",
                 Phase::ShortBreak => "Short Break",
                 Phase::LongBreak => "Long Break",
             }.into(),
-            duration: self.total_duration().as_secs() / 60,
-            completed,
+            duration: total.as_secs() / 60,
+            elapsed_secs: focused.as_secs(),
+            paused_secs: self.clock.paused(at).as_secs(),
+            end_reason: reason,
+            interruptions,
+            task: if self.phase == Phase::Work { self.stats.active_task } else { None },
+            project: if self.phase == Phase::Work { self.stats.active_project.clone() } else { None },
+            tags: if self.phase == Phase::Work { self.stats.active_tags.clone() } else { Vec::new() },
         });
-        
-        if self.stats.session_history.len() > MAX_HISTORY {
-            self.stats.session_history.remove(0);
-        }
+        self.logged_elapsed = Duration::ZERO;
+        self.needs_save = true;
     }
     
-    fn update_weekly_stats(&mut self) {
-        let weekday = chrono::Local::now().weekday().num_days_from_monday() as usize;
-        if weekday < 7 {
-            self.stats.weekly_sessions[weekday] += 1;
+    /// Logs the phase so far when rtimer exits mid-phase. The saved timer
+    /// state remembers how much was logged, so resuming doesn't count it
+    /// twice. Fails if Timewarrior couldn't be stopped, which leaves it
+    /// tracking after rtimer is gone; the phase is logged either way.
+    fn record_quit(&mut self) -> io::Result<()> {
+        if self.remote.is_some() || self.read_only {
+            return Ok(());
         }
+        let now = SystemTime::now();
+        let elapsed = self.clock.elapsed(now).min(self.total_duration());
+        self.record_session(now, EndReason::Quit);
+        self.logged_elapsed = elapsed;
+        self.timewarrior.stop_at(now.into())
     }
     
     fn check_extended_break(&mut self) {
@@ -434,29 +1450,98 @@ impl AppState {
         }
     }
 
-    fn update(&mut self) {
-        if !self.paused && self.time_remaining > Duration::ZERO {
-            self.time_remaining = self.time_remaining.saturating_sub(TICK_RATE);
-            
-            if self.phase == Phase::Work {
-                self.check_extended_break();
-            }
-            
-            if self.time_remaining.as_secs() == 0 {
-                self.next_phase();
-            }
+    /// Warns once in the evening if nothing done today would keep the
+    /// streak going.
+    fn check_streak(&mut self) {
+        let Some(hour) = self.streak_reminder_hour else { return };
+        let now = chrono::Local::now();
+        let today = now.date_naive();
+        if now.hour() < hour || self.stats.streak_warned == Some(today.to_string()) {
+            return;
         }
+        self.stats.streak_warned = Some(today.to_string());
+        self.needs_save = true;
         
-        self.animation_frame = self.animation_frame.wrapping_add(1) % 20;
-        
-        if self.needs_save && self.last_save.elapsed() >= AUTO_SAVE_INTERVAL {
-            self.save_stats();
-            self.last_save = Instant::now();
+        let (streak, _) = self.stats.streaks(today, self.streak_min_sessions);
+        let left = self.streak_min_sessions.max(1).saturating_sub(self.stats.day_totals(today).work_sessions);
+        if streak > 0 && left > 0 {
+            notify(
+                "🔥 Streak at Risk",
+                &format!("{} more session{} today to keep your {}-day streak.", left, if left == 1 { "" } else { "s" }, streak),
+                self.sound_enabled,
+            );
+        }
+    }
+
+    fn update(&mut self) {
+        self.animation_frame = self.animation_frame.wrapping_add(1) % 20;
+        
+        // A daemon owns the timer; we only mirror it.
+        if self.remote.is_some() {
+            self.sync_remote();
+            return;
+        }
+        
+        let now = SystemTime::now();
+        if let Ok(gap) = now.duration_since(self.last_update)
+            && gap >= SUSPEND_THRESHOLD
+        {
+            self.handle_suspend(self.last_update, gap);
+        }
+        self.last_update = now;
+        
+        if !self.clock.is_paused() && self.phase == Phase::Work {
+            self.check_extended_break();
+        }
+        self.check_streak();
+        
+        while let Some(end) = self.clock.end_time(self.total_duration())
+            && end <= now
+            && !self.total_duration().is_zero()
+        {
+            self.advance_phase(end, EndReason::Completed);
+        }
+        self.suspended = false;
+        
+        if self.needs_save && self.last_save.elapsed() >= AUTO_SAVE_INTERVAL {
+            self.save_stats();
+            self.last_save = Instant::now();
+        }
+    }
+    
+    /// Called when the wall clock jumped by `gap` since the last update at
+    /// `since`, which means the machine was asleep (or the process stopped).
+    fn handle_suspend(&mut self, since: SystemTime, gap: Duration) {
+        if self.clock.is_paused() {
+            return;
+        }
+        
+        match self.suspend_policy {
+            SuspendPolicy::CountElapsed => self.suspended = true,
+            SuspendPolicy::Pause => self.clock.pause(since),
+            SuspendPolicy::Ask => {
+                self.clock.pause(since);
+                self.pending_suspend = Some(gap);
+            }
+        }
+    }
+    
+    fn resolve_suspend(&mut self, choice: SuspendChoice) {
+        if self.pending_suspend.take().is_some() {
+            match choice {
+                SuspendChoice::Count => {
+                    self.clock.discard_pause();
+                    self.suspended = true;
+                }
+                SuspendChoice::Discard => self.clock.resume(SystemTime::now()),
+                SuspendChoice::StayPaused => {}
+            }
         }
     }
     
-    fn save_config(&self) {
-        let config = Config {
+    fn config(&self) -> Config {
+        Config {
+            schema_version: Config::VERSION,
             work_duration: self.work_duration.as_secs_f64() / 60.0,
             rest_duration: self.rest_duration.as_secs_f64() / 60.0,
             long_break_duration: self.long_break_duration.as_secs_f64() / 60.0,
@@ -465,26 +1550,89 @@ impl AppState {
             theme: self.theme_name.clone(),
             auto_start_next: self.auto_start_next,
             extended_break_reminder_hours: self.extended_break_hours,
-        };
-        let _ = save_json(&get_path("config.json"), &config);
+            suspend_policy: self.suspend_policy,
+            storage: self.storage_backend,
+            daily_goal_sessions: self.daily_goal,
+            weekly_goal_hours: self.weekly_goal_hours,
+            streak_min_sessions: self.streak_min_sessions,
+            streak_reminder_hour: self.streak_reminder_hour,
+            calendar_file: self.calendar_file.clone(),
+            timewarrior: self.timewarrior.mode,
+            timewarrior_dry_run: self.timewarrior.dry_run,
+        }
+    }
+    
+    fn apply_config(&mut self, config: &Config) {
+        self.work_duration = Duration::from_secs_f64(config.work_duration * 60.0);
+        self.rest_duration = Duration::from_secs_f64(config.rest_duration * 60.0);
+        self.long_break_duration = Duration::from_secs_f64(config.long_break_duration * 60.0);
+        self.sessions_before_long_break = config.sessions_before_long_break;
+        self.sound_enabled = config.sound_enabled;
+        self.theme_name = config.theme.clone();
+        self.theme = get_theme(&config.theme);
+        self.auto_start_next = config.auto_start_next;
+        self.extended_break_hours = config.extended_break_reminder_hours;
+        self.suspend_policy = config.suspend_policy;
+        self.daily_goal = config.daily_goal_sessions;
+        self.weekly_goal_hours = config.weekly_goal_hours;
+        self.streak_min_sessions = config.streak_min_sessions;
+        self.streak_reminder_hour = config.streak_reminder_hour;
+        self.calendar_file = config.calendar_file.clone();
+        self.timewarrior.mode = config.timewarrior;
+        self.timewarrior.dry_run = config.timewarrior_dry_run;
     }
     
+    fn save_config(&mut self) {
+        if self.remote.is_some() {
+            self.dispatch(Request::Configure { config: self.config() });
+        } else if !self.read_only {
+            let _ = save_json(&get_path("config.json"), &self.config());
+        }
+    }
+    
+    /// Writes the statistics if anything changed since the last save;
+    /// stats.json holds the whole history, so rewriting it is not free.
     fn save_stats(&mut self) {
-        if save_json(&get_path("stats.json"), &self.stats).is_ok() {
+        if self.read_only || !self.needs_save {
+            return;
+        }
+        if let Some(store) = self.store.as_mut()
+            && store.save(&self.stats).is_ok()
+        {
             self.needs_save = false;
         }
     }
     
     fn save_on_quit(&mut self) {
+        self.save_state(false);
+    }
+    
+    /// Saves statistics and the timer. A `detached` timer has nothing left
+    /// running it, so its clock is saved too and keeps counting until a
+    /// daemon or `--resume` picks it up.
+    fn save_state(&mut self, detached: bool) {
+        if self.remote.is_some() || self.read_only {
+            return;
+        }
         self.save_stats();
-        
-        let state = TimerState {
-            time_remaining_secs: self.time_remaining.as_secs(),
+        let _ = save_json(&get_path("timer_state.json"), &self.timer_state(detached));
+    }
+
+    fn timer_state(&self, detached: bool) -> TimerState {
+        let minutes = |d: Duration| Some(d.as_secs_f64() / 60.0);
+        TimerState {
+            schema_version: TimerState::VERSION,
+            time_remaining_secs: self.time_remaining().as_secs(),
             phase: self.phase.to_str().into(),
             session_count: self.session_count,
-            paused: self.paused,
-        };
-        let _ = save_json(&get_path("timer_state.json"), &state);
+            paused: self.paused(),
+            logged_secs: self.logged_elapsed.as_secs(),
+            work_duration: minutes(self.work_duration),
+            rest_duration: minutes(self.rest_duration),
+            long_break_duration: minutes(self.long_break_duration),
+            clock: detached.then(|| self.clock.save()),
+            interruptions: self.interruptions.clone(),
+        }
     }
 }
 
@@ -493,6 +1641,50 @@ impl AppState {
 // ============================================================================
 
 fn handle_input(key: event::KeyEvent, app: &mut AppState) -> bool {
+    // Suspend prompt
+    if app.pending_suspend.is_some() {
+        match key.code {
+            KeyCode::Char('c') | KeyCode::Char('C') => app.dispatch(Request::ResolveSuspend { choice: SuspendChoice::Count }),
+            KeyCode::Char('d') | KeyCode::Char('D') => app.dispatch(Request::ResolveSuspend { choice: SuspendChoice::Discard }),
+            KeyCode::Char('p') | KeyCode::Char('P') | KeyCode::Esc => app.dispatch(Request::ResolveSuspend { choice: SuspendChoice::StayPaused }),
+            _ => {}
+        }
+        return false;
+    }
+    
+    if let Some(prompt) = &mut app.interruption_prompt {
+        match key.code {
+            KeyCode::Tab => prompt.kind = InterruptionKind::cycle(prompt.kind),
+            KeyCode::Char(c) => prompt.reason.push(c),
+            KeyCode::Backspace => { prompt.reason.pop(); }
+            KeyCode::Enter => {
+                let InterruptionPrompt { kind, reason } = std::mem::take(prompt);
+                app.interruption_prompt = None;
+                if kind.is_some() || !reason.trim().is_empty() {
+                    app.dispatch(Request::DescribeInterruption { kind, reason: reason.trim().into() });
+                }
+            }
+            KeyCode::Esc => app.interruption_prompt = None,
+            _ => {}
+        }
+        return false;
+    }
+    
+    if let Some(input) = &mut app.label_input {
+        match key.code {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => { input.pop(); }
+            KeyCode::Enter => {
+                let (project, tags) = parse_label(input);
+                app.label_input = None;
+                app.dispatch(Request::SetLabel { project, tags });
+            }
+            KeyCode::Esc => app.label_input = None,
+            _ => {}
+        }
+        return false;
+    }
+    
     // Input modes
     if matches!(app.notes_mode, NotesMode::Adding | NotesMode::Editing) {
         match key.code {
@@ -500,22 +1692,14 @@ fn handle_input(key: event::KeyEvent, app: &mut AppState) -> bool {
             KeyCode::Backspace => { app.notes_input.pop(); }
             KeyCode::Enter => {
                 if !app.notes_input.trim().is_empty() {
+                    let content = app.notes_input.trim().to_string();
                     if app.notes_mode == NotesMode::Editing {
-                        if let Some(idx) = app.selected_note_index {
-                            if idx < app.stats.notes.len() {
-                                app.stats.notes[idx].content = app.notes_input.trim().into();
-                                app.needs_save = true;
-                            }
+                        if let Some(index) = app.selected_note_index {
+                            app.dispatch(Request::EditNote { index, content });
                         }
                     } else {
-                        let now = chrono::Local::now();
-                        app.stats.notes.push(Note {
-                            timestamp: now.to_rfc3339(),
-                            content: app.notes_input.trim().into(),
-                            phase: app.phase.to_str().into(),
-                        });
-                        app.selected_note_index = Some(app.stats.notes.len() - 1);
-                        app.needs_save = true;
+                        app.dispatch(Request::AddNote { content });
+                        app.selected_note_index = app.stats.notes.len().checked_sub(1);
                     }
                 }
                 app.notes_mode = NotesMode::Viewing;
@@ -530,6 +1714,56 @@ fn handle_input(key: event::KeyEvent, app: &mut AppState) -> bool {
         return false;
     }
     
+    if matches!(app.tasks_mode, TasksMode::Adding | TasksMode::Editing) {
+        match key.code {
+            KeyCode::Char(c) => app.tasks_input.push(c),
+            KeyCode::Backspace => { app.tasks_input.pop(); }
+            KeyCode::Enter => {
+                let name = app.tasks_input.trim().to_string();
+                if !name.is_empty() {
+                    match (app.tasks_mode, app.selected_task()) {
+                        (TasksMode::Editing, Some(task)) => {
+                            let id = task.id;
+                            app.dispatch(Request::UpdateTask { id, name: Some(name), estimate: None, done: None });
+                        }
+                        _ => {
+                            let (name, estimate) = parse_task(&name);
+                            app.dispatch(Request::AddTask { name, estimate });
+                            app.selected_task_index = app.stats.tasks.len().checked_sub(1);
+                        }
+                    }
+                }
+                app.tasks_mode = TasksMode::Viewing;
+                app.tasks_input.clear();
+            }
+            KeyCode::Esc => {
+                app.tasks_mode = TasksMode::Viewing;
+                app.tasks_input.clear();
+            }
+            _ => {}
+        }
+        return false;
+    }
+    
+    if app.tasks_mode == TasksMode::ConfirmingDelete {
+        match key.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                if let Some(id) = app.selected_task().map(|t| t.id) {
+                    app.dispatch(Request::DeleteTask { id });
+                    app.selected_task_index = app.selected_task_index
+                        .map(|idx| idx.min(app.stats.tasks.len().saturating_sub(1)))
+                        .filter(|_| !app.stats.tasks.is_empty());
+                }
+                app.tasks_mode = TasksMode::Viewing;
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                app.tasks_mode = TasksMode::Viewing;
+            }
+            _ => {}
+        }
+        return false;
+    }
+    
     if app.settings_editing {
         match key.code {
             KeyCode::Char(c) => app.settings_input.push(c),
@@ -548,16 +1782,13 @@ fn handle_input(key: event::KeyEvent, app: &mut AppState) -> bool {
     if app.notes_mode == NotesMode::ConfirmingDelete {
         match key.code {
             KeyCode::Char('y') | KeyCode::Char('Y') => {
-                if let Some(idx) = app.selected_note_index {
-                    if idx < app.stats.notes.len() {
-                        app.stats.notes.remove(idx);
-                        app.selected_note_index = if app.stats.notes.is_empty() {
-                            None
-                        } else {
-                            Some(idx.min(app.stats.notes.len() - 1))
-                        };
-                        app.needs_save = true;
-                    }
+                if let Some(index) = app.selected_note_index {
+                    app.dispatch(Request::DeleteNote { index });
+                    app.selected_note_index = if app.stats.notes.is_empty() {
+                        None
+                    } else {
+                        Some(index.min(app.stats.notes.len() - 1))
+                    };
                 }
                 app.notes_mode = NotesMode::Viewing;
             }
@@ -572,11 +1803,69 @@ fn handle_input(key: event::KeyEvent, app: &mut AppState) -> bool {
     // View-specific handlers
     match app.current_view {
         View::Notes => handle_notes_view(key, app),
+        View::Tasks => handle_tasks_view(key, app),
         View::Settings => handle_settings_view(key, app),
         _ => handle_main_view(key, app),
     }
 }
 
+fn handle_tasks_view(key: event::KeyEvent, app: &mut AppState) -> bool {
+    let selected = app.selected_task().cloned();
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('q') => {
+            app.current_view = View::Timer;
+            app.tasks_mode = TasksMode::Viewing;
+        }
+        KeyCode::Char('a') | KeyCode::Char('n') => {
+            app.tasks_mode = TasksMode::Adding;
+            app.tasks_input.clear();
+        }
+        KeyCode::Char('e') if selected.is_some() => {
+            app.tasks_input = selected.map(|t| t.name).unwrap_or_default();
+            app.tasks_mode = TasksMode::Editing;
+        }
+        KeyCode::Char('d') if selected.is_some() => {
+            app.tasks_mode = TasksMode::ConfirmingDelete;
+        }
+        KeyCode::Enter => {
+            if let Some(task) = selected {
+                let id = (app.stats.active_task != Some(task.id) && !task.done).then_some(task.id);
+                app.dispatch(Request::SelectTask { id });
+                app.current_view = View::Timer;
+            }
+        }
+        KeyCode::Char('x') | KeyCode::Char(' ') => {
+            if let Some(task) = selected {
+                app.dispatch(Request::UpdateTask { id: task.id, name: None, estimate: None, done: Some(!task.done) });
+            }
+        }
+        KeyCode::Char('+') | KeyCode::Char('=') => {
+            if let Some(task) = selected {
+                app.dispatch(Request::UpdateTask { id: task.id, name: None, estimate: Some(task.estimate + 1), done: None });
+            }
+        }
+        KeyCode::Char('-') => {
+            if let Some(task) = selected {
+                app.dispatch(Request::UpdateTask { id: task.id, name: None, estimate: Some(task.estimate.saturating_sub(1).max(1)), done: None });
+            }
+        }
+        KeyCode::Down | KeyCode::Char('j') if !app.stats.tasks.is_empty() => {
+            app.selected_task_index = Some(match app.selected_task_index {
+                Some(idx) => (idx + 1).min(app.stats.tasks.len() - 1),
+                None => 0,
+            });
+        }
+        KeyCode::Up | KeyCode::Char('k') if !app.stats.tasks.is_empty() => {
+            app.selected_task_index = Some(match app.selected_task_index {
+                Some(idx) => idx.saturating_sub(1),
+                None => 0,
+            });
+        }
+        _ => {}
+    }
+    false
+}
+
 fn handle_notes_view(key: event::KeyEvent, app: &mut AppState) -> bool {
     match key.code {
         KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('t') => {
@@ -588,33 +1877,27 @@ fn handle_notes_view(key: event::KeyEvent, app: &mut AppState) -> bool {
             app.notes_input.clear();
         }
         KeyCode::Char('e') => {
-            if let Some(idx) = app.selected_note_index {
-                if idx < app.stats.notes.len() {
-                    app.notes_input = app.stats.notes[idx].content.clone();
-                    app.notes_mode = NotesMode::Editing;
-                }
+            if let Some(idx) = app.selected_note_index
+                && idx < app.stats.notes.len()
+            {
+                app.notes_input = app.stats.notes[idx].content.clone();
+                app.notes_mode = NotesMode::Editing;
             }
         }
-        KeyCode::Char('d') => {
-            if app.selected_note_index.is_some() {
-                app.notes_mode = NotesMode::ConfirmingDelete;
-            }
+        KeyCode::Char('d') if app.selected_note_index.is_some() => {
+            app.notes_mode = NotesMode::ConfirmingDelete;
         }
-        KeyCode::Down | KeyCode::Char('j') => {
-            if !app.stats.notes.is_empty() {
-                app.selected_note_index = Some(match app.selected_note_index {
-                    Some(idx) => (idx + 1).min(app.stats.notes.len() - 1),
-                    None => 0,
-                });
-            }
+        KeyCode::Down | KeyCode::Char('j') if !app.stats.notes.is_empty() => {
+            app.selected_note_index = Some(match app.selected_note_index {
+                Some(idx) => (idx + 1).min(app.stats.notes.len() - 1),
+                None => 0,
+            });
         }
-        KeyCode::Up | KeyCode::Char('k') => {
-            if !app.stats.notes.is_empty() {
-                app.selected_note_index = Some(match app.selected_note_index {
-                    Some(idx) => idx.saturating_sub(1),
-                    None => app.stats.notes.len() - 1,
-                });
-            }
+        KeyCode::Up | KeyCode::Char('k') if !app.stats.notes.is_empty() => {
+            app.selected_note_index = Some(match app.selected_note_index {
+                Some(idx) => idx.saturating_sub(1),
+                None => app.stats.notes.len() - 1,
+            });
         }
         _ => {}
     }
@@ -645,18 +1928,26 @@ fn handle_settings_view(key: event::KeyEvent, app: &mut AppState) -> bool {
                     app.auto_start_next = !app.auto_start_next;
                     app.save_config();
                 }
+                SettingsField::SuspendPolicy => {
+                    app.suspend_policy = app.suspend_policy.next();
+                    app.save_config();
+                }
                 _ => {}
             }
         }
-        KeyCode::Left | KeyCode::Char('h') => {
-            if app.settings_field == SettingsField::Theme {
-                cycle_theme(app, false);
-            }
+        KeyCode::Left | KeyCode::Char('h') if app.settings_field == SettingsField::Theme => {
+            cycle_theme(app, false);
         }
-        KeyCode::Right | KeyCode::Char('l') => {
-            if app.settings_field == SettingsField::Theme {
-                cycle_theme(app, true);
-            }
+        KeyCode::Right | KeyCode::Char('l') if app.settings_field == SettingsField::Theme => {
+            cycle_theme(app, true);
+        }
+        KeyCode::Left | KeyCode::Char('h') if app.settings_field == SettingsField::SuspendPolicy => {
+            app.suspend_policy = app.suspend_policy.prev();
+            app.save_config();
+        }
+        KeyCode::Right | KeyCode::Char('l') if app.settings_field == SettingsField::SuspendPolicy => {
+            app.suspend_policy = app.suspend_policy.next();
+            app.save_config();
         }
         _ => {}
     }
@@ -679,17 +1970,26 @@ fn handle_main_view(key: event::KeyEvent, app: &mut AppState) -> bool {
     }
     
     match key.code {
-        KeyCode::Char(' ') => app.paused = !app.paused,
-        KeyCode::Char('r') => {
-            app.time_remaining = app.total_duration();
-            app.paused = false;
+        KeyCode::Char(' ') => {
+            app.dispatch(Request::Toggle);
+            if app.paused() && app.phase == Phase::Work {
+                app.interruption_prompt = Some(InterruptionPrompt::default());
+            }
         }
-        KeyCode::Char('n') => app.next_phase(),
+        KeyCode::Char('r') => app.dispatch(Request::Reset),
+        KeyCode::Char('n') => app.dispatch(Request::Skip),
         KeyCode::Char('d') => app.current_view = View::Settings,
         KeyCode::Char('t') => {
             app.current_view = View::Notes;
             app.notes_mode = NotesMode::Viewing;
         }
+        KeyCode::Char('k') => {
+            app.current_view = View::Tasks;
+            app.tasks_mode = TasksMode::Viewing;
+        }
+        KeyCode::Char('p') => {
+            app.label_input = Some(format_label(app.stats.active_project.as_deref(), &app.stats.active_tags));
+        }
         KeyCode::Char('h') | KeyCode::Char('?') => {
             app.current_view = if app.current_view == View::Help {
                 View::Timer
@@ -708,13 +2008,43 @@ fn handle_main_view(key: event::KeyEvent, app: &mut AppState) -> bool {
             app.current_view = match app.current_view {
                 View::StatsSummary => View::StatsDetailed,
                 View::StatsDetailed => View::StatsHistory,
-                View::StatsHistory => View::StatsSummary,
+                View::StatsHistory => View::StatsInterruptions,
+                View::StatsInterruptions => View::StatsProjects,
+                View::StatsProjects => View::StatsHeatmap,
+                View::StatsHeatmap => View::StatsRhythm,
+                View::StatsRhythm => View::StatsSummary,
                 _ => app.current_view,
             };
         }
+        KeyCode::Left if app.current_view == View::StatsDetailed => {
+            app.stats_weeks_back += 1;
+        }
+        KeyCode::Right if app.current_view == View::StatsDetailed => {
+            app.stats_weeks_back = app.stats_weeks_back.saturating_sub(1);
+        }
+        KeyCode::Left if app.current_view == View::StatsRhythm => {
+            app.rhythm_range = app.rhythm_range.saturating_sub(1);
+        }
+        KeyCode::Right if app.current_view == View::StatsRhythm => {
+            app.rhythm_range = (app.rhythm_range + 1).min(RHYTHM_RANGES.len() - 1);
+        }
+        KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down if app.current_view == View::StatsHeatmap => {
+            let days = match key.code {
+                KeyCode::Left => -7,
+                KeyCode::Right => 7,
+                KeyCode::Up => -1,
+                _ => 1,
+            };
+            let (first, today) = heatmap_range();
+            app.heatmap_day = (app.heatmap_day + chrono::Duration::days(days)).clamp(first, today);
+        }
         KeyCode::Char('e') => {
-            if matches!(app.current_view, View::StatsSummary | View::StatsDetailed | View::StatsHistory) {
-                let _ = export_csv(&app.stats);
+            if matches!(app.current_view, View::StatsSummary | View::StatsDetailed | View::StatsHistory | View::StatsInterruptions | View::StatsProjects | View::StatsHeatmap | View::StatsRhythm) {
+                let message = match export::all_to_data_dir(&app.stats) {
+                    Ok(dir) => format!("📤 Exported sessions, notes, tasks and labels as CSV to {}", dir.display()),
+                    Err(e) => format!("⚠ Export failed: {}", e),
+                };
+                app.status_message = Some((message, Instant::now()));
             }
         }
         _ => {}
@@ -729,6 +2059,10 @@ fn start_editing(app: &mut AppState) {
         SettingsField::RestDuration => format_mins(app.rest_duration),
         SettingsField::LongBreakDuration => format_mins(app.long_break_duration),
         SettingsField::SessionsBeforeLongBreak => app.sessions_before_long_break.to_string(),
+        SettingsField::DailyGoal => app.daily_goal.to_string(),
+        SettingsField::WeeklyGoal => format!("{}", app.weekly_goal_hours),
+        SettingsField::StreakMinSessions => app.streak_min_sessions.to_string(),
+        SettingsField::StreakReminder => app.streak_reminder_hour.map_or_else(String::new, |h| h.to_string()),
         SettingsField::ExtendedBreakReminder => {
             let h = app.extended_break_hours;
             if h.fract() == 0.0 { format!("{}", h as u64) } else { format!("{:.1}", h) }
@@ -754,43 +2088,80 @@ fn apply_setting(app: &mut AppState) {
     
     match app.settings_field {
         SettingsField::WorkDuration => {
-            if let Ok(m) = parsed {
-                if (0.0..=240.0).contains(&m) {
-                    app.work_duration = Duration::from_secs_f64(m * 60.0);
-                    app.save_config();
-                }
+            if let Ok(m) = parsed
+                && (0.0..=240.0).contains(&m)
+            {
+                app.work_duration = Duration::from_secs_f64(m * 60.0);
+                app.save_config();
             }
         }
         SettingsField::RestDuration => {
-            if let Ok(m) = parsed {
-                if (0.0..=60.0).contains(&m) {
-                    app.rest_duration = Duration::from_secs_f64(m * 60.0);
-                    app.save_config();
-                }
+            if let Ok(m) = parsed
+                && (0.0..=60.0).contains(&m)
+            {
+                app.rest_duration = Duration::from_secs_f64(m * 60.0);
+                app.save_config();
             }
         }
         SettingsField::LongBreakDuration => {
-            if let Ok(m) = parsed {
-                if (0.0..=120.0).contains(&m) {
-                    app.long_break_duration = Duration::from_secs_f64(m * 60.0);
-                    app.save_config();
-                }
+            if let Ok(m) = parsed
+                && (0.0..=120.0).contains(&m)
+            {
+                app.long_break_duration = Duration::from_secs_f64(m * 60.0);
+                app.save_config();
             }
         }
         SettingsField::SessionsBeforeLongBreak => {
-            if let Ok(s) = app.settings_input.parse::<u32>() {
-                if (1..=10).contains(&s) {
-                    app.sessions_before_long_break = s;
-                    app.save_config();
-                }
+            if let Ok(s) = app.settings_input.parse::<u32>()
+                && (1..=10).contains(&s)
+            {
+                app.sessions_before_long_break = s;
+                app.save_config();
+            }
+        }
+        SettingsField::DailyGoal => {
+            if let Ok(n) = app.settings_input.parse::<u32>()
+                && n <= 50
+            {
+                app.daily_goal = n;
+                app.save_config();
+            }
+        }
+        SettingsField::WeeklyGoal => {
+            if let Ok(h) = parsed
+                && (0.0..=168.0).contains(&h)
+            {
+                app.weekly_goal_hours = h;
+                app.save_config();
+            }
+        }
+        SettingsField::StreakMinSessions => {
+            if let Ok(n) = app.settings_input.parse::<u32>()
+                && (1..=50).contains(&n)
+            {
+                app.streak_min_sessions = n;
+                app.save_config();
+            }
+        }
+        SettingsField::StreakReminder => {
+            // An empty value turns the reminder off.
+            let input = app.settings_input.trim();
+            if input.is_empty() {
+                app.streak_reminder_hour = None;
+                app.save_config();
+            } else if let Ok(h) = input.parse::<u32>()
+                && h < 24
+            {
+                app.streak_reminder_hour = Some(h);
+                app.save_config();
             }
         }
         SettingsField::ExtendedBreakReminder => {
-            if let Ok(h) = parsed {
-                if (0.5..=8.0).contains(&h) {
-                    app.extended_break_hours = h;
-                    app.save_config();
-                }
+            if let Ok(h) = parsed
+                && (0.5..=8.0).contains(&h)
+            {
+                app.extended_break_hours = h;
+                app.save_config();
             }
         }
         _ => {}
@@ -828,17 +2199,138 @@ fn render_ui(f: &mut Frame, app: &AppState) {
             View::StatsSummary => render_stats_summary(f, app),
             View::StatsDetailed => render_stats_detailed(f, app),
             View::StatsHistory => render_stats_history(f, app),
+            View::StatsInterruptions => render_stats_interruptions(f, app),
+            View::StatsProjects => render_stats_projects(f, app),
+            View::StatsHeatmap => render_stats_heatmap(f, app),
+            View::StatsRhythm => render_stats_rhythm(f, app),
             View::Settings => render_settings(f, app),
             View::Notes => render_notes(f, app),
+            View::Tasks => render_tasks(f, app),
         }
     }
+    
+    if let Some(prompt) = &app.interruption_prompt {
+        render_interruption_prompt(f, app, prompt);
+    }
+    if let Some(input) = &app.label_input {
+        render_label_prompt(f, app, input);
+    }
+    if let Some(gap) = app.pending_suspend {
+        render_suspend_prompt(f, app, gap);
+    }
+    if let Some((message, since)) = &app.status_message
+        && since.elapsed() < STATUS_MESSAGE_TIME
+    {
+        let size = f.size();
+        let area = Rect { y: size.bottom().saturating_sub(1), height: 1.min(size.height), ..size };
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(message.as_str()).alignment(Alignment::Center).style(Style::default().fg(Color::Yellow)),
+            area
+        );
+    }
+}
+
+fn render_label_prompt(f: &mut Frame, app: &AppState, input: &str) {
+    let area = centered_rect(50, 30, f.size());
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled("📁 PROJECT & TAGS", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(Span::styled("e.g. rtimer #rust #review  •  empty to clear", Style::default().fg(Color::DarkGray))),
+        Line::from(""),
+        Line::from(Span::styled(format!("{}█", input), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(vec![span_key("Enter", app), Span::raw(" Save  •  "), span_key("Esc", app), Span::raw(" Cancel")]),
+    ];
+    
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default()
+                .title(" Label Sessions ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(app.theme.border_color))),
+        area
+    );
+}
+
+fn render_interruption_prompt(f: &mut Frame, app: &AppState, prompt: &InterruptionPrompt) {
+    let area = centered_rect(50, 30, f.size());
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled("⏸ INTERRUPTED", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("Kind: "),
+            Span::styled(InterruptionKind::label(prompt.kind), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::styled("  (Tab to change)", Style::default().fg(Color::DarkGray)),
+        ]),
+        Line::from(vec![
+            Span::raw("Reason: "),
+            Span::styled(format!("{}█", prompt.reason), Style::default().fg(Color::White)),
+        ]),
+        Line::from(""),
+        Line::from(vec![span_key("Enter", app), Span::raw(" Save  •  "), span_key("Esc", app), Span::raw(" Skip")]),
+    ];
+    
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default()
+                .title(" Interruption ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(app.theme.border_color))),
+        area
+    );
+}
+
+fn render_suspend_prompt(f: &mut Frame, app: &AppState, gap: Duration) {
+    let area = centered_rect(50, 40, f.size());
+    let mins = gap.as_secs() / 60;
+    let slept = if mins >= 60 {
+        format!("{}h {:02}m", mins / 60, mins % 60)
+    } else {
+        format!("{}m {:02}s", mins, gap.as_secs() % 60)
+    };
+    
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled("💤 WELCOME BACK", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(format!("The system was asleep for {}.", slept)),
+        Line::from("The timer was paused when it went to sleep."),
+        Line::from(""),
+        Line::from(vec![span_key("C", app), Span::raw(" Count the sleep as elapsed")]),
+        Line::from(vec![span_key("D", app), Span::raw(" Discard it and resume")]),
+        Line::from(vec![span_key("P", app), Span::raw(" Stay paused")]),
+    ];
+    
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .block(Block::default()
+                .title(" Suspend Detected ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(app.theme.border_color))),
+        area
+    );
 }
 
 fn render_minimized(f: &mut Frame, app: &AppState) {
     let area = centered_rect(40, 30, f.size());
-    let secs = app.time_remaining.as_secs();
+    let secs = app.time_remaining().as_secs();
     let time_str = format!("{:02}:{:02}", secs / 60, secs % 60);
-    let status = if app.paused { "⏸ PAUSED" } else { "▶ RUNNING" };
+    let status = if app.paused() { "⏸ PAUSED" } else { "▶ RUNNING" };
     
     let lines = vec![
         Line::from(""),
@@ -849,7 +2341,7 @@ fn render_minimized(f: &mut Frame, app: &AppState) {
             .fg(app.phase_color()).add_modifier(Modifier::BOLD))),
         Line::from(""),
         Line::from(Span::styled(status, Style::default()
-            .fg(if app.paused { Color::Yellow } else { Color::Green }))),
+            .fg(if app.paused() { Color::Yellow } else { Color::Green }))),
         Line::from(""),
         Line::from(""),
         Line::from(Span::styled("Press M to restore", Style::default()
@@ -881,6 +2373,12 @@ fn render_timer(f: &mut Frame, app: &AppState) {
         .border_style(Style::default().fg(app.theme.border_color))
         .title(Span::styled(" 🍅 RTIMER ", Style::default()
             .fg(app.theme.accent_color).add_modifier(Modifier::BOLD)));
+    let header = if app.read_only {
+        header.title(block::Title::from(Span::styled(" 🔒 READ-ONLY ", Style::default().fg(Color::Yellow)))
+            .alignment(Alignment::Right))
+    } else {
+        header
+    };
     f.render_widget(header, chunks[0]);
     
     // Main content
@@ -907,7 +2405,7 @@ fn render_timer(f: &mut Frame, app: &AppState) {
     );
     
     // Timer
-    let secs = app.time_remaining.as_secs();
+    let secs = app.time_remaining().as_secs();
     let time_str = format!("{:02}:{:02}", secs / 60, secs % 60);
     f.render_widget(
         Paragraph::new(time_str)
@@ -925,7 +2423,9 @@ fn render_timer(f: &mut Frame, app: &AppState) {
     f.render_widget(Paragraph::new(date_lines).alignment(Alignment::Center), sections[5]);
     
     // Status
-    let status = if app.paused {
+    let status = if app.remote.as_ref().is_some_and(|r| !r.connected()) {
+        "⚠  DAEMON UNREACHABLE".to_string()
+    } else if app.paused() {
         format!("⏸  PAUSED{}", ".".repeat((app.animation_frame / 5) as usize % 4))
     } else {
         format!("{} RUNNING", if app.animation_frame < 10 { "●" } else { "○" })
@@ -933,7 +2433,7 @@ fn render_timer(f: &mut Frame, app: &AppState) {
     f.render_widget(
         Paragraph::new(status)
             .style(Style::default()
-                .fg(if app.paused { Color::Yellow } else { Color::Green })
+                .fg(if app.paused() { Color::Yellow } else { Color::Green })
                 .add_modifier(Modifier::BOLD))
             .alignment(Alignment::Center),
         sections[7]
@@ -949,14 +2449,49 @@ fn render_timer(f: &mut Frame, app: &AppState) {
     );
     
     // Session info
-    let session_text = format!(
-        "Session {} of {}  •  {} completed today",
+    let (today, week) = app.stats.goal_totals(chrono::Local::now().date_naive());
+    let mut session_line = vec![Span::raw(format!(
+        "Session {} of {}  •  ",
         ((app.session_count - 1) % app.sessions_before_long_break) + 1,
         app.sessions_before_long_break,
-        app.stats.sessions_today
-    );
+    ))];
+    let goal_style = |met: bool| match met {
+        true => Style::default().fg(Color::Green).add_modifier(Modifier::BOLD),
+        false => Style::default(),
+    };
+    if app.daily_goal > 0 {
+        session_line.push(Span::styled(
+            format!("🏆 {}/{} today", today.work_sessions, app.daily_goal),
+            goal_style(app.daily_goal_met(&today)),
+        ));
+    } else {
+        session_line.push(Span::raw(format!("{} completed today", today.work_sessions)));
+    }
+    if app.weekly_goal_hours > 0.0 {
+        session_line.push(Span::raw("  •  "));
+        session_line.push(Span::styled(
+            format!("{:.1}/{}h this week", week.work_secs as f64 / 3600.0, app.weekly_goal_hours),
+            goal_style(app.weekly_goal_met(&week)),
+        ));
+    }
+    let mut task_line = match app.stats.active_task.and_then(|id| app.stats.task(id)) {
+        Some(task) => vec![
+            Span::raw(format!("📋 {}  ", task.name)),
+            Span::styled(
+                format!("🍅 {}/{}", app.stats.task_pomodoros(task.id), task.estimate),
+                Style::default().fg(app.theme.accent_color),
+            ),
+        ],
+        None => vec![Span::styled("No task selected  •  K to pick one", Style::default().fg(Color::DarkGray))],
+    };
+    let label = format_label(app.stats.active_project.as_deref(), &app.stats.active_tags);
+    if !label.is_empty() {
+        task_line.push(Span::raw("  •  "));
+        task_line.push(Span::styled(format!("📁 {}", label), Style::default().fg(Color::Cyan)));
+    }
+    let task_line = Line::from(task_line);
     f.render_widget(
-        Paragraph::new(session_text).style(Style::default().fg(Color::Gray)).alignment(Alignment::Center),
+        Paragraph::new(vec![Line::from(session_line), task_line]).style(Style::default().fg(Color::Gray)).alignment(Alignment::Center),
         sections[11]
     );
     
@@ -970,6 +2505,8 @@ fn render_timer(f: &mut Frame, app: &AppState) {
         ]),
         Line::from(vec![
             span_key("T", app), Span::raw(" Notes  •  "),
+            span_key("K", app), Span::raw(" Tasks  •  "),
+            span_key("P", app), Span::raw(" Project  •  "),
             span_key("S", app), Span::raw(" Stats  •  "),
             span_key("D", app), Span::raw(" Settings  •  "),
             span_key("H", app), Span::raw(" Help  •  "),
@@ -994,17 +2531,22 @@ fn render_help(f: &mut Frame, app: &AppState) {
         Line::from(Span::styled("⌨️  KEYBOARD SHORTCUTS", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
         Line::from(""),
         Line::from("  Timer Controls:"),
-        help_line("Space", "Toggle pause/resume"),
+        help_line("Space", "Toggle pause/resume (pausing focus asks why)"),
         help_line("R", "Reset current timer"),
         help_line("N", "Skip to next phase"),
         help_line("M", "Minimize to compact view"),
         Line::from(""),
         Line::from("  Navigation:"),
         help_line("T", "Open notes view"),
+        help_line("K", "Open tasks (Enter picks the active one)"),
+        help_line("P", "Set project and #tags for new sessions"),
         help_line("S", "Open statistics"),
         help_line("D", "Open settings"),
         help_line("H / ?", "Toggle help"),
         help_line("Tab", "Cycle through stat views"),
+        help_line("← / →", "Previous/next week in weekly stats"),
+        help_line("Arrows", "Pick a day in the heatmap"),
+        help_line("← / →", "Shorter/longer range in the rhythm view"),
         Line::from(""),
         Line::from("  Notes View:"),
         help_line("A / N", "Add new note"),
@@ -1043,25 +2585,73 @@ fn help_line<'a>(key: &'a str, desc: &'a str) -> Line<'a> {
 
 fn render_stats_summary(f: &mut Frame, app: &AppState) {
     let area = centered_rect(70, 85, f.size());
+    let today = app.stats.day_totals(chrono::Local::now().date_naive());
+    let totals = app.stats.totals();
+    let open_tasks = app.stats.tasks.iter().filter(|t| !t.done).count();
+    let done_tasks: Vec<&Task> = app.stats.tasks.iter().filter(|t| t.done).collect();
+    let estimated: u32 = done_tasks.iter().map(|t| t.estimate).sum();
+    let actual: u32 = done_tasks.iter().map(|t| app.stats.task_pomodoros(t.id)).sum();
+    let date = chrono::Local::now().date_naive();
+    let (streak, longest_streak) = app.stats.streaks(date, app.streak_min_sessions);
+    let days = |n: u32| format!("{} day{}", n, if n == 1 { "" } else { "s" });
+    let hit_rate = |goal: String, (hits, periods): (usize, usize), unit: &str| match periods {
+        0 => format!("{}, no history yet", goal),
+        _ => format!("{}, met {} of {} {} ({:.0}%)", goal, hits, periods, unit, hits as f64 / periods as f64 * 100.0),
+    };
+    // Goals are off unless set, and only shown when they are.
+    let mut goals = Vec::new();
+    if app.daily_goal > 0 {
+        goals.push(stat_line("Daily", hit_rate(
+            format!("{} sessions", app.daily_goal),
+            app.stats.goal_hits(date, |d| d, |t| app.daily_goal_met(t)),
+            "days",
+        )));
+    }
+    if app.weekly_goal_hours > 0.0 {
+        goals.push(stat_line("Weekly", hit_rate(
+            format!("{}h focus", app.weekly_goal_hours),
+            app.stats.goal_hits(date, |d| d.week(chrono::Weekday::Mon).first_day(), |t| app.weekly_goal_met(t)),
+            "weeks",
+        )));
+    }
     
-    let lines = vec![
+    let mut lines = vec![
         Line::from(""),
         Line::from(Span::styled("📊 STATISTICS OVERVIEW", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
         Line::from(""),
-        Line::from(Span::styled("  Press Tab to cycle views  •  E to export CSV", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC))),
+        Line::from(Span::styled("  Press Tab to cycle views  •  E to export CSV files", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC))),
         Line::from(""),
         Line::from(""),
         Line::from(Span::styled("  📅 Today:", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))),
-        stat_line("Sessions completed", app.stats.sessions_today.to_string()),
+        stat_line("Sessions completed", today.work_sessions.to_string()),
+        stat_line("Focus time", format_hours(today.work_secs)),
         Line::from(""),
         Line::from(Span::styled("  📈 All Time:", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))),
-        stat_line("Total sessions", app.stats.total_sessions.to_string()),
-        stat_line("Total focus time", format!("{:.1} hours", app.stats.total_work_time as f64 / 60.0)),
-        stat_line("Total break time", format!("{:.1} hours", app.stats.total_break_time as f64 / 60.0)),
+        stat_line("Total sessions", totals.work_sessions.to_string()),
+        stat_line("Total focus time", format_hours(totals.work_secs)),
+        stat_line("Total break time", format_hours(totals.break_secs)),
+        Line::from(""),
+    ];
+    if !goals.is_empty() {
+        lines.push(Line::from(Span::styled("  🏆 Goals:", Style::default().fg(Color::LightRed).add_modifier(Modifier::BOLD))));
+        lines.extend(goals);
+        lines.push(Line::from(""));
+    }
+    lines.extend([
+        Line::from(Span::styled(
+            format!("  🔥 Streak ({}+ sessions a day):", app.streak_min_sessions.max(1)),
+            Style::default().fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+        )),
+        stat_line("Current", days(streak)),
+        stat_line("Longest", days(longest_streak)),
         Line::from(""),
         Line::from(Span::styled("  📝 Notes:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))),
         stat_line("Total notes", app.stats.notes.len().to_string()),
-    ];
+        Line::from(""),
+        Line::from(Span::styled("  📋 Tasks:", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD))),
+        stat_line("Open / done", format!("{} / {}", open_tasks, done_tasks.len())),
+        stat_line("Done tasks, estimated vs actual", format!("{} vs {} 🍅", estimated, actual)),
+    ]);
     
     f.render_widget(
         Paragraph::new(lines)
@@ -1075,6 +2665,19 @@ fn render_stats_summary(f: &mut Frame, app: &AppState) {
     );
 }
 
+/// Short form like `23m` or `1h05m` for the history view.
+fn format_secs(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+fn format_hours(secs: u64) -> String {
+    format!("{:.1} hours", secs as f64 / 3600.0)
+}
+
 fn stat_line(label: &str, value: String) -> Line<'static> {
     Line::from(vec![
         Span::raw(format!("     {}: ", label)),
@@ -1084,26 +2687,62 @@ fn stat_line(label: &str, value: String) -> Line<'static> {
 
 fn render_stats_detailed(f: &mut Frame, app: &AppState) {
     let area = centered_rect(70, 85, f.size());
+    let today = chrono::Local::now().date_naive();
+    let monday = week_start(today, app.stats_weeks_back);
     
     let mut lines = vec![
         Line::from(""),
-        Line::from(Span::styled("📊 WEEKLY BREAKDOWN", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
+        Line::from(Span::styled(
+            format!("📊 WEEK {} • {} – {}", monday.iso_week().week(), monday.format("%b %-d"), (monday + chrono::Duration::days(6)).format("%b %-d, %Y")),
+            Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD),
+        )),
         Line::from(""),
-        Line::from(Span::styled("  Sessions per day this week:", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC))),
+        Line::from(Span::styled("  Sessions per day  •  ←/→ previous/next week", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC))),
         Line::from(""),
     ];
     
-    let days = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
-    let max = *app.stats.weekly_sessions.iter().max().unwrap_or(&1).max(&1);
+    let week = app.stats.week_totals(monday);
+    let max = week.iter().map(|t| t.work_sessions).max().unwrap_or(1).max(1);
     
-    for (i, &count) in app.stats.weekly_sessions.iter().enumerate() {
-        let width = (count as f64 / max as f64 * 30.0) as usize;
-        lines.push(Line::from(vec![
-            Span::styled(format!("  {} ", days[i]), Style::default().fg(Color::Gray)),
+    for (i, totals) in week.iter().enumerate() {
+        let date = monday + chrono::Duration::days(i as i64);
+        let width = (totals.work_sessions as f64 / max as f64 * 30.0) as usize;
+        let label_style = if date == today {
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
+        } else if date > today {
+            Style::default().fg(Color::DarkGray)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        let mut line = vec![
+            Span::styled(format!("  {} {:>2} ", date.format("%a"), date.day()), label_style),
             Span::styled("█".repeat(width), Style::default().fg(app.theme.accent_color)),
-            Span::raw(format!(" {}", count)),
-        ]));
-    }
+        ];
+        if date <= today {
+            line.push(Span::raw(format!(" {}", totals.work_sessions)));
+            if totals.work_secs > 0 {
+                line.push(Span::styled(format!("  ({})", format_hours(totals.work_secs)), Style::default().fg(Color::DarkGray)));
+            }
+        }
+        lines.push(Line::from(line));
+    }
+    
+    let week_sum = |days: &[Totals; 7]| days.iter().fold((0, 0), |(n, secs), t| (n + t.work_sessions, secs + t.work_secs));
+    let (sessions, secs) = week_sum(&week);
+    lines.push(Line::from(""));
+    lines.push(stat_line("Week total", format!("{} sessions, {}", sessions, format_hours(secs))));
+    
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("  Previous weeks:", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC))));
+    for back in 1..=4 {
+        let monday = week_start(today, app.stats_weeks_back + back);
+        let (sessions, secs) = week_sum(&app.stats.week_totals(monday));
+        lines.push(Line::from(vec![
+            Span::styled(format!("  Week {:>2} ", monday.iso_week().week()), Style::default().fg(Color::Gray)),
+            Span::raw(format!(" {} sessions", sessions)),
+            Span::styled(format!("  ({})", format_hours(secs)), Style::default().fg(Color::DarkGray)),
+        ]));
+    }
     
     f.render_widget(
         Paragraph::new(lines)
@@ -1117,6 +2756,116 @@ fn render_stats_detailed(f: &mut Frame, app: &AppState) {
     );
 }
 
+fn render_stats_projects(f: &mut Frame, app: &AppState) {
+    let area = centered_rect(75, 85, f.size());
+    let (projects, tags) = app.stats.label_totals();
+    
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled("📁 PROJECTS & TAGS", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(Span::styled("  Focus time per label  •  P on the timer screen sets them", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC))),
+        Line::from(""),
+    ];
+    
+    for (title, totals, prefix) in [("  Projects:", &projects, ""), ("  Tags:", &tags, "#")] {
+        lines.push(Line::from(Span::styled(title, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))));
+        if totals.is_empty() {
+            lines.push(Line::from(Span::styled("     None yet", Style::default().fg(Color::DarkGray))));
+        }
+        let mut rows: Vec<_> = totals.iter().collect();
+        rows.sort_by_key(|(_, t)| std::cmp::Reverse(t.work_secs));
+        let max = rows.first().map_or(1, |(_, t)| t.work_secs.max(1));
+        for (name, t) in rows.iter().take(8) {
+            let width = (t.work_secs as f64 / max as f64 * 20.0) as usize;
+            lines.push(Line::from(vec![
+                Span::styled(format!("     {:<20} ", format!("{}{}", prefix, name)), Style::default().fg(Color::Gray)),
+                Span::styled(format!("{:<20}", "█".repeat(width)), Style::default().fg(app.theme.accent_color)),
+                Span::raw(format!(" {} sessions, {}", t.work_sessions, format_secs(t.work_secs))),
+            ]));
+        }
+        lines.push(Line::from(""));
+    }
+    
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(Block::default()
+                .title(" Projects & Tags ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(app.theme.border_color))),
+        area
+    );
+}
+
+fn render_stats_interruptions(f: &mut Frame, app: &AppState) {
+    let area = centered_rect(75, 85, f.size());
+    let work: Vec<&SessionRecord> = app.stats.session_history.iter().filter(|s| s.is_work()).collect();
+    let all: Vec<&Interruption> = work.iter().flat_map(|s| &s.interruptions).collect();
+    
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled("⚡ INTERRUPTIONS", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+    ];
+    
+    if all.is_empty() {
+        lines.push(Line::from(Span::styled("  No interruptions logged yet. Pausing a focus session logs one.", Style::default().fg(Color::DarkGray))));
+    } else {
+        let count = |kind| all.iter().filter(|i| i.kind == kind).count();
+        let lost: u64 = all.iter().map(|i| i.duration_secs).sum();
+        lines.push(stat_line("Total", format!(
+            "{} ({} internal, {} external, {} unspecified)",
+            all.len(), count(Some(InterruptionKind::Internal)), count(Some(InterruptionKind::External)), count(None),
+        )));
+        lines.push(stat_line("Per focus session", format!("{:.1}", all.len() as f64 / work.len() as f64)));
+        lines.push(stat_line("Time paused", format_secs(lost)));
+        
+        // Most common reasons, ignoring case.
+        let mut reasons: BTreeMap<String, (usize, &str)> = BTreeMap::new();
+        for i in all.iter().filter(|i| !i.reason.is_empty()) {
+            reasons.entry(i.reason.to_lowercase()).or_insert((0, &i.reason)).0 += 1;
+        }
+        let mut reasons: Vec<_> = reasons.into_values().collect();
+        reasons.sort_by_key(|&(n, _)| std::cmp::Reverse(n));
+        if !reasons.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("  Most common reasons:", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC))));
+            for (n, reason) in reasons.iter().take(5) {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {:>3}× ", n), Style::default().fg(Color::Cyan)),
+                    Span::raw(reason.to_string()),
+                ]));
+            }
+        }
+    }
+    
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled("  Recent focus sessions:", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC))));
+    for s in work.iter().rev().take(10) {
+        let when = chrono::DateTime::parse_from_rfc3339(&s.timestamp)
+            .map(|t| t.format("%m-%d %H:%M").to_string())
+            .unwrap_or_else(|_| "Unknown".into());
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {} ", when), Style::default().fg(Color::Gray)),
+            Span::styled("▮".repeat(s.interruptions.len()), Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}", s.interruptions.len())),
+        ]));
+    }
+    
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(Block::default()
+                .title(" Interruptions ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(app.theme.border_color))),
+        area
+    );
+}
+
 fn render_stats_history(f: &mut Frame, app: &AppState) {
     let area = centered_rect(75, 85, f.size());
     
@@ -1132,32 +2881,7 @@ fn render_stats_history(f: &mut Frame, app: &AppState) {
         lines.push(Line::from(Span::styled("  No sessions yet!", Style::default().fg(Color::DarkGray))));
     } else {
         for s in app.stats.session_history.iter().rev().take(15) {
-            let dt = s.timestamp.split('T')
-                .next()
-                .and_then(|d| s.timestamp.split('T').nth(1)?.split('.').next().map(|t| format!("{} {}", d, &t[..5])))
-                .unwrap_or_else(|| "Unknown".into());
-            
-            let icon = match s.phase_type.as_str() {
-                "Work" => "🎯",
-                "Short Break" => "☕",
-                "Long Break" => "🌴",
-                _ => "📝",
-            };
-            
-            let (status, color) = if s.completed { ("✓", Color::Green) } else { ("⏸", Color::Yellow) };
-            
-            lines.push(Line::from(vec![
-                Span::raw("  "),
-                Span::raw(icon),
-                Span::raw(" "),
-                Span::styled(dt, Style::default().fg(Color::Gray)),
-                Span::raw(" • "),
-                Span::styled(&s.phase_type, Style::default().fg(Color::White)),
-                Span::raw(" • "),
-                Span::styled(format!("{}m", s.duration), Style::default().fg(Color::Cyan)),
-                Span::raw(" "),
-                Span::styled(status, Style::default().fg(color)),
-            ]));
+            lines.push(session_line(s, "%Y-%m-%d "));
         }
     }
     
@@ -1173,6 +2897,268 @@ fn render_stats_history(f: &mut Frame, app: &AppState) {
     );
 }
 
+/// One session as listed in the history and heatmap views; `date_fmt`
+/// prefixes the start and end times.
+fn session_line<'a>(s: &'a SessionRecord, date_fmt: &str) -> Line<'a> {
+    let time = |t: &str| chrono::DateTime::parse_from_rfc3339(t).ok();
+    let dt = match (s.started.as_deref().and_then(time), time(&s.timestamp)) {
+        (Some(start), Some(end)) => format!("{}{}–{}", start.format(date_fmt), start.format("%H:%M"), end.format("%H:%M")),
+        (None, Some(end)) => format!("{}  –{}", end.format(date_fmt), end.format("%H:%M")),
+        _ => "Unknown".into(),
+    };
+    
+    let icon = match s.phase_type.as_str() {
+        "Work" => "🎯",
+        "Short Break" => "☕",
+        "Long Break" => "🌴",
+        _ => "📝",
+    };
+    
+    let (status, color) = match s.end_reason {
+        EndReason::Completed => ("✓", Color::Green),
+        EndReason::Suspended => ("💤", Color::Blue),
+        _ => ("⏸", Color::Yellow),
+    };
+    
+    Line::from(vec![
+        Span::raw("  "),
+        Span::raw(icon),
+        Span::raw(" "),
+        Span::styled(dt, Style::default().fg(Color::Gray)),
+        Span::raw(" • "),
+        Span::styled(&s.phase_type, Style::default().fg(Color::White)),
+        Span::raw(" • "),
+        Span::styled(format_secs(s.elapsed_secs), Style::default().fg(Color::Cyan)),
+        Span::styled(format!("/{}m", s.duration), Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            if s.paused_secs > 0 { format!(" (+{} paused)", format_secs(s.paused_secs)) } else { String::new() },
+            Style::default().fg(Color::DarkGray),
+        ),
+        Span::raw(" "),
+        Span::styled(format!("{} {}", status, s.end_reason.label()), Style::default().fg(color)),
+    ])
+}
+
+/// The first and last day of the heatmap: a year of whole weeks up to today.
+fn heatmap_range() -> (chrono::NaiveDate, chrono::NaiveDate) {
+    let today = chrono::Local::now().date_naive();
+    (week_start(today, 52), today)
+}
+
+/// `color` faded towards the background; `level` runs from 1 (faint) to 4.
+fn shade(color: Color, level: u32) -> Color {
+    match color {
+        Color::Rgb(r, g, b) => {
+            let mix = |c: u8| (30.0 + (c as f64 - 30.0) * level as f64 / 4.0) as u8;
+            Color::Rgb(mix(r), mix(g), mix(b))
+        }
+        color => color,
+    }
+}
+
+fn render_stats_heatmap(f: &mut Frame, app: &AppState) {
+    let area = centered_rect(75, 85, f.size());
+    let (first, today) = heatmap_range();
+    let days = app.stats.daily_totals(first, today);
+    let max = days.values().map(|t| t.work_secs).max().unwrap_or(0).max(1);
+    
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled("🗓  FOCUS HEATMAP", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(Span::styled("  ←→ Week  •  ↑↓ Day  •  Focus time per day over the last year", Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC))),
+        Line::from(""),
+    ];
+    
+    // Month names above the week each month starts in
+    let mut months = String::new();
+    for week in 0..53 {
+        let monday = first + chrono::Duration::weeks(week);
+        if months.len() > week as usize {
+            continue;
+        }
+        if (monday - chrono::Duration::weeks(1)).month() != monday.month() {
+            months.push_str(&monday.format("%b").to_string());
+        } else {
+            months.push(' ');
+        }
+    }
+    lines.push(Line::from(Span::styled(format!("      {}", months), Style::default().fg(Color::Gray))));
+    
+    for (weekday, label) in ["Mon", "", "Wed", "", "Fri", "", ""].into_iter().enumerate() {
+        let mut row = vec![Span::styled(format!("  {:<4}", label), Style::default().fg(Color::Gray))];
+        for week in 0..53 {
+            let date = first + chrono::Duration::days(week * 7 + weekday as i64);
+            let secs = days.get(&date).map_or(0, |t| t.work_secs);
+            let (symbol, mut style) = match date {
+                d if d > today => (" ", Style::default()),
+                _ if secs == 0 => ("·", Style::default().fg(Color::DarkGray)),
+                _ => {
+                    let level = (secs * 4).div_ceil(max) as u32;
+                    ("■", Style::default().fg(shade(app.theme.work_color, level)))
+                }
+            };
+            if date == app.heatmap_day {
+                style = style.bg(app.theme.accent_color);
+            }
+            row.push(Span::styled(symbol, style));
+        }
+        lines.push(Line::from(row));
+    }
+    
+    lines.push(Line::from(""));
+    let mut legend = vec![Span::styled("      Less ", Style::default().fg(Color::DarkGray)), Span::styled("·", Style::default().fg(Color::DarkGray))];
+    legend.extend((1..=4).map(|level| Span::styled("■", Style::default().fg(shade(app.theme.work_color, level)))));
+    legend.push(Span::styled(" More", Style::default().fg(Color::DarkGray)));
+    lines.push(Line::from(legend));
+    
+    // The selected day
+    let day = app.heatmap_day;
+    let totals = days.get(&day).copied().unwrap_or_default();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        format!("  {}  •  {} sessions  •  {} focus", day.format("%A, %B %-d, %Y"), totals.work_sessions, format_secs(totals.work_secs)),
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+    )));
+    let sessions: Vec<&SessionRecord> = app.stats.session_history.iter().filter(|s| s.date() == Some(day)).collect();
+    if sessions.is_empty() {
+        lines.push(Line::from(Span::styled("     No sessions", Style::default().fg(Color::DarkGray))));
+    }
+    for s in sessions {
+        lines.push(session_line(s, ""));
+    }
+    let on_day = |t: &str| chrono::DateTime::parse_from_rfc3339(t).is_ok_and(|t| t.date_naive() == day);
+    for note in app.stats.notes.iter().filter(|n| on_day(&n.timestamp)) {
+        lines.push(Line::from(vec![
+            Span::raw("  📝 "),
+            Span::styled(note.content.as_str(), Style::default().fg(Color::Yellow)),
+        ]));
+    }
+    
+    f.render_widget(
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(Block::default()
+                .title(" Heatmap ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(app.theme.border_color))),
+        area
+    );
+}
+
+/// Bar color for a completion rate: green when most sessions are finished,
+/// red when most are skipped.
+fn completion_color(rate: Option<f64>) -> Color {
+    match rate {
+        None => Color::DarkGray,
+        Some(r) if r >= 0.8 => Color::Green,
+        Some(r) if r >= 0.5 => Color::Yellow,
+        Some(_) => Color::Red,
+    }
+}
+
+fn render_stats_rhythm(f: &mut Frame, app: &AppState) {
+    let area = centered_rect(80, 85, f.size());
+    let (days, range) = RHYTHM_RANGES[app.rhythm_range];
+    let from = days.map(|d| chrono::Local::now().date_naive() - chrono::Duration::days(d - 1));
+    let (hours, weekdays) = app.stats.rhythm(from);
+    
+    let block = Block::default()
+        .title(" Rhythm ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(app.theme.border_color));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(5),
+            Constraint::Min(8),
+            Constraint::Min(8),
+            Constraint::Length(5),
+        ])
+        .split(inner);
+    
+    f.render_widget(Paragraph::new(vec![
+        Line::from(""),
+        Line::from(Span::styled("⏰ WHEN YOU FOCUS", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(format!("  Completed sessions, {}  •  ←→ Range  •  Finished: ", range), Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC)),
+            Span::styled("■ 80%+ ", Style::default().fg(Color::Green)),
+            Span::styled("■ 50%+ ", Style::default().fg(Color::Yellow)),
+            Span::styled("■ less", Style::default().fg(Color::Red)),
+        ]),
+    ]), sections[0]);
+    
+    let bar = |label: String, c: &Completion| {
+        Bar::default()
+            .value(c.completed as u64)
+            .label(Line::from(label))
+            .style(Style::default().fg(completion_color(c.rate())))
+            .value_style(Style::default().fg(Color::Black).bg(completion_color(c.rate())))
+    };
+    // The widest bars that fit all 24 hours; below two columns a bar can't
+    // hold its label, so the labels go.
+    let (bar_width, bar_gap) = [(2, 1), (1, 1), (1, 0)].into_iter()
+        .find(|&(w, g)| 24 * w + 23 * g <= sections[1].width)
+        .unwrap_or((1, 0));
+    let hour_bars: Vec<Bar> = hours.iter().enumerate().map(|(h, c)| {
+        bar(if bar_width >= 2 { format!("{:02}", h) } else { String::new() }, c)
+    }).collect();
+    f.render_widget(
+        BarChart::default()
+            .block(Block::default().title(Span::styled(" By hour started ", Style::default().fg(Color::Cyan))))
+            .data(BarGroup::default().bars(&hour_bars))
+            .bar_width(bar_width)
+            .bar_gap(bar_gap),
+        sections[1]
+    );
+    
+    const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    let (bar_width, bar_gap) = [(8, 2), (8, 1), (3, 1)].into_iter()
+        .find(|&(w, g)| 7 * w + 6 * g <= sections[2].width)
+        .unwrap_or((3, 0));
+    let weekday_bars: Vec<Bar> = weekdays.iter().zip(WEEKDAYS).map(|(c, day)| {
+        let label = match c.rate() {
+            Some(rate) if bar_width >= 8 => format!("{} {:.0}%", day, rate * 100.0),
+            _ => day.to_string(),
+        };
+        bar(label, c)
+    }).collect();
+    f.render_widget(
+        BarChart::default()
+            .block(Block::default().title(Span::styled(" By weekday ", Style::default().fg(Color::Cyan))))
+            .data(BarGroup::default().bars(&weekday_bars))
+            .bar_width(bar_width)
+            .bar_gap(bar_gap),
+        sections[2]
+    );
+    
+    let mut best: Vec<(usize, &Completion)> = hours.iter().enumerate().filter(|(_, c)| c.completed > 0).collect();
+    best.sort_by_key(|(_, c)| std::cmp::Reverse(c.completed));
+    let best: Vec<String> = best.iter().take(3).map(|(h, c)| {
+        format!("{:02}:00 ({}, {:.0}%)", h, c.completed, c.rate().unwrap_or(0.0) * 100.0)
+    }).collect();
+    let total = hours.iter().fold(Completion::default(), |mut t, c| {
+        t.completed += c.completed;
+        t.skipped += c.skipped;
+        t
+    });
+    f.render_widget(Paragraph::new(vec![
+        Line::from(""),
+        stat_line("Most productive hours", if best.is_empty() { "not enough data".into() } else { best.join(", ") }),
+        stat_line("Completion rate", match total.rate() {
+            Some(rate) => format!("{:.0}% ({} completed, {} skipped)", rate * 100.0, total.completed, total.skipped),
+            None => "no sessions in range".into(),
+        }),
+    ]), sections[3]);
+}
+
 fn render_settings(f: &mut Frame, app: &AppState) {
     let area = centered_rect(70, 85, f.size());
     
@@ -1190,10 +3176,24 @@ fn render_settings(f: &mut Frame, app: &AppState) {
         (SettingsField::RestDuration, "☕ Rest Duration", format_mins(app.rest_duration) + " min"),
         (SettingsField::LongBreakDuration, "🌴 Long Break", format_mins(app.long_break_duration) + " min"),
         (SettingsField::SessionsBeforeLongBreak, "🔄 Sessions Before Long Break", format!("{} sessions", app.sessions_before_long_break)),
+        (SettingsField::DailyGoal, "🏆 Daily Goal", match app.daily_goal {
+            0 => "Off".into(),
+            n => format!("{} sessions", n),
+        }),
+        (SettingsField::WeeklyGoal, "🏆 Weekly Goal", match app.weekly_goal_hours {
+            0.0 => "Off".into(),
+            h => format!("{} focus hours", h),
+        }),
+        (SettingsField::StreakMinSessions, "🔥 Streak Day", format!("{}+ sessions", app.streak_min_sessions.max(1))),
+        (SettingsField::StreakReminder, "🔥 Streak Reminder", match app.streak_reminder_hour {
+            None => "Off".into(),
+            Some(h) => format!("From {:02}:00", h),
+        }),
         (SettingsField::Theme, "🎨 Theme", format!("< {} >", app.theme_name)),
         (SettingsField::SoundEnabled, "🔔 Sound", if app.sound_enabled { "ON" } else { "OFF" }.into()),
         (SettingsField::AutoStartNext, "▶️  Auto-Start", if app.auto_start_next { "ON" } else { "OFF" }.into()),
         (SettingsField::ExtendedBreakReminder, "⏰ Break Reminder", format!("After {:.1}h", app.extended_break_hours)),
+        (SettingsField::SuspendPolicy, "💤 On Suspend", format!("< {} >", app.suspend_policy.label())),
     ];
     
     for (field, label, value) in settings {
@@ -1240,6 +3240,91 @@ fn render_settings(f: &mut Frame, app: &AppState) {
     );
 }
 
+fn render_tasks(f: &mut Frame, app: &AppState) {
+    let area = centered_rect(80, 85, f.size());
+    
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled("📋 TASKS", Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD))),
+        Line::from(""),
+    ];
+    
+    let help = match app.tasks_mode {
+        TasksMode::Viewing => "  a: Add • e: Rename • d: Delete • Enter: Work on it • x: Done • +/-: Estimate • Esc: Close",
+        TasksMode::Adding => "  Type the task, ~N to estimate N pomodoros, and press Enter to save  •  Esc to cancel",
+        TasksMode::Editing => "  Rename the task and press Enter to save  •  Esc to cancel",
+        TasksMode::ConfirmingDelete => "  Y: Confirm  •  N/Esc: Cancel",
+    };
+    lines.push(Line::from(Span::styled(help, Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC))));
+    lines.push(Line::from(""));
+    
+    if matches!(app.tasks_mode, TasksMode::Adding | TasksMode::Editing) {
+        let title = if app.tasks_mode == TasksMode::Adding { "✏️  NEW TASK" } else { "✏️  RENAMING" };
+        lines.push(Line::from(Span::styled(format!("  {}", title), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))));
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![
+            Span::raw("  "),
+            Span::styled(&app.tasks_input, Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled("█", Style::default().fg(Color::Green)),
+        ]));
+        lines.push(Line::from(""));
+        lines.push(Line::from("  ─────────────────────────────────────────────────────────────────────"));
+        lines.push(Line::from(""));
+    }
+    
+    if app.tasks_mode == TasksMode::ConfirmingDelete
+        && let Some(task) = app.selected_task()
+    {
+        lines.push(Line::from(Span::styled("  ⚠️  DELETE TASK?", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))));
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![Span::raw("  "), Span::styled(&task.name, Style::default().fg(Color::White))]));
+        lines.push(Line::from(Span::styled("  Sessions already spent on it stay in the history.", Style::default().fg(Color::DarkGray))));
+        lines.push(Line::from(""));
+        lines.push(Line::from("  ─────────────────────────────────────────────────────────────────────"));
+        lines.push(Line::from(""));
+    }
+    
+    if app.stats.tasks.is_empty() {
+        lines.push(Line::from(Span::styled("  No tasks yet! Press 'a' to add one.", Style::default().fg(Color::Gray))));
+    } else {
+        lines.push(Line::from(Span::styled(format!("    {:<45}  done / estimate", "Task"), Style::default().fg(Color::DarkGray))));
+        for (idx, task) in app.stats.tasks.iter().enumerate() {
+            let selected = app.selected_task_index == Some(idx);
+            let active = app.stats.active_task == Some(task.id);
+            let actual = app.stats.task_pomodoros(task.id);
+            
+            let prefix = if selected { "► " } else { "  " };
+            let style = if task.done {
+                Style::default().fg(Color::DarkGray).add_modifier(Modifier::CROSSED_OUT)
+            } else if selected {
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            let count_color = if actual > task.estimate { Color::Red } else { Color::Green };
+            
+            lines.push(Line::from(vec![
+                Span::styled(prefix, Style::default().fg(app.theme.accent_color).add_modifier(Modifier::BOLD)),
+                Span::raw(if task.done { "✓ " } else if active { "▶ " } else { "  " }),
+                Span::styled(format!("{:<45}", task.name), style),
+                Span::styled(format!("  🍅 {:>2}", actual), Style::default().fg(count_color)),
+                Span::styled(format!(" / {}", task.estimate), Style::default().fg(Color::Gray)),
+            ]));
+        }
+    }
+    
+    f.render_widget(
+        Paragraph::new(lines)
+            .block(Block::default()
+                .title(" Tasks ")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(app.theme.border_color))),
+        area
+    );
+}
+
 fn render_notes(f: &mut Frame, app: &AppState) {
     let area = centered_rect(80, 85, f.size());
     
@@ -1272,17 +3357,16 @@ fn render_notes(f: &mut Frame, app: &AppState) {
         lines.push(Line::from(""));
     }
     
-    if app.notes_mode == NotesMode::ConfirmingDelete {
-        if let Some(idx) = app.selected_note_index {
-            if idx < app.stats.notes.len() {
-                lines.push(Line::from(Span::styled("  ⚠️  DELETE NOTE?", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))));
-                lines.push(Line::from(""));
-                lines.push(Line::from(vec![Span::raw("  "), Span::styled(&app.stats.notes[idx].content, Style::default().fg(Color::White))]));
-                lines.push(Line::from(""));
-                lines.push(Line::from("  ─────────────────────────────────────────────────────────────────────"));
-                lines.push(Line::from(""));
-            }
-        }
+    if app.notes_mode == NotesMode::ConfirmingDelete
+        && let Some(idx) = app.selected_note_index
+        && idx < app.stats.notes.len()
+    {
+        lines.push(Line::from(Span::styled("  ⚠️  DELETE NOTE?", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))));
+        lines.push(Line::from(""));
+        lines.push(Line::from(vec![Span::raw("  "), Span::styled(&app.stats.notes[idx].content, Style::default().fg(Color::White))]));
+        lines.push(Line::from(""));
+        lines.push(Line::from("  ─────────────────────────────────────────────────────────────────────"));
+        lines.push(Line::from(""));
     }
     
     if app.stats.notes.is_empty() {
@@ -1429,90 +3513,107 @@ fn get_theme(name: &str) -> Theme {
     }
 }
 
-fn get_path(filename: &str) -> PathBuf {
-    let mut path = PathBuf::from(".");
-    path.push("rtimer");
-    let _ = fs::create_dir_all(&path);
-    path.push(filename);
-    path
-}
-
-fn load_json<T: for<'de> Deserialize<'de> + Default>(path: &PathBuf) -> T {
-    fs::read_to_string(path)
-        .ok()
-        .and_then(|s| serde_json::from_str(&s).ok())
-        .unwrap_or_default()
-}
-
-fn save_json<T: Serialize>(path: &PathBuf, data: &T) -> io::Result<()> {
-    fs::write(path, serde_json::to_string_pretty(data)?)
-}
-
-fn reset_daily_stats(stats: &mut Statistics) {
-    let today = chrono::Local::now().format(DAILY_FMT).to_string();
-    if stats.last_session_date != today {
-        stats.sessions_today = 0;
-        stats.last_session_date = today;
-        stats.weekly_sessions.rotate_left(1);
-        stats.weekly_sessions[6] = 0;
-    }
-}
-
-fn export_csv(stats: &Statistics) -> io::Result<()> {
-    let mut csv = format!(
-        "Date,Total Sessions,Sessions Today,Work Time (h),Break Time (h)\n{},{},{},{:.2},{:.2}\n\n",
-        stats.last_session_date,
-        stats.total_sessions,
-        stats.sessions_today,
-        stats.total_work_time as f64 / 60.0,
-        stats.total_break_time as f64 / 60.0
-    );
-    
-    csv.push_str("Session History\nTimestamp,Phase,Duration (min),Completed\n");
-    for s in stats.session_history.iter().rev().take(50) {
-        csv.push_str(&format!("{},{},{},{}\n", s.timestamp, s.phase_type, s.duration, if s.completed { "Yes" } else { "No" }));
-    }
-    
-    csv.push_str("\nNotes\nTimestamp,Phase,Content\n");
-    for n in stats.notes.iter().rev() {
-        let content = if n.content.contains(',') || n.content.contains('"') {
-            format!("\"{}\"", n.content.replace('"', "\"\""))
-        } else {
-            n.content.clone()
-        };
-        csv.push_str(&format!("{},{},{}\n", n.timestamp, n.phase, content));
-    }
-    
-    fs::write(get_path("stats_export.csv"), csv)
-}
-
 // ============================================================================
 // Main
 // ============================================================================
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let mut config = load_json::<Config>(&get_path("config.json"));
+    
+    if let Some(dir) = &args.data_dir {
+        storage::set_data_dir(dir.clone());
+    } else {
+        let oneshot = args.command.as_ref().is_some_and(|c| !matches!(c, Command::Daemon));
+        storage::migrate_legacy_dirs(oneshot);
+    }
+    
+    // Attach to a running daemon instead of driving our own timer.
+    if args.command.is_none()
+        && let Some(remote) = Remote::connect()
+    {
+        let config = load_json::<Config>(&get_path("config.json"))?.unwrap_or_default();
+        let mut app = AppState::new(config, Statistics::default(), None);
+        app.remote = Some(remote);
+        app.sync_remote();
+        return run_tui(app);
+    }
+    
+    // The TUI and the daemon own the data directory for as long as they run.
+    // Taking the lock first means only they write upgraded files back.
+    let mut read_only = false;
+    let _lock = if matches!(args.command, None | Some(Command::Daemon)) {
+        match storage::lock_data_dir()? {
+            Some(lock) => Some(lock),
+            None if args.read_only && args.command.is_none() => {
+                read_only = true;
+                None
+            }
+            None => return Err(format!(
+                "{}; start `rtimer daemon` to share one timer between terminals, or pass --read-only",
+                storage::busy_message()
+            ).into()),
+        }
+    } else {
+        None
+    };
+    
+    let mut config = load_json::<Config>(&get_path("config.json"))?.unwrap_or_default();
+    
+    // One-shot commands without a daemon operate on the saved timer state.
+    let oneshot = args.command.as_ref().is_some_and(|c| !matches!(c, Command::Daemon));
+    let saved = if args.resume || oneshot {
+        load_json::<TimerState>(&get_path("timer_state.json"))?
+    } else {
+        None
+    };
+    if let Some(saved) = &saved {
+        if let Some(w) = saved.work_duration { config.work_duration = w; }
+        if let Some(r) = saved.rest_duration { config.rest_duration = r; }
+        if let Some(l) = saved.long_break_duration { config.long_break_duration = l; }
+    }
     
     // CLI overrides
     if let Some(w) = args.work { config.work_duration = w; }
     if let Some(r) = args.rest { config.rest_duration = r; }
     if let Some(l) = args.long_break { config.long_break_duration = l; }
     if let Some(s) = args.sessions { config.sessions_before_long_break = s; }
-    if let Some(t) = args.theme { config.theme = t; }
+    if let Some(t) = &args.theme { config.theme = t.clone(); }
     if args.no_sound { config.sound_enabled = false; }
+    if let Some(p) = args.on_suspend { config.suspend_policy = p; }
     
-    let mut stats = load_json::<Statistics>(&get_path("stats.json"));
-    reset_daily_stats(&mut stats);
-    
-    let saved = if args.resume {
-        load_json::<Option<TimerState>>(&get_path("timer_state.json"))
-    } else {
-        None
-    };
+    let mut store = storage::open_store(config.storage)?;
+    let stats = store.load()?;
     
     let mut app = AppState::new(config, stats, saved);
-    
+    app.store = Some(store);
+    app.read_only = read_only;
+    
+    match args.command {
+        None => run_tui(app),
+        Some(Command::Daemon) => daemon::run_daemon(app),
+        Some(Command::Bar { format, once }) => bar::run(app, format, once),
+        Some(Command::Stats { rebuild, force, json }) => commands::stats(app, rebuild, force, json),
+        Some(Command::Storage { backend, ref from }) => commands::switch_storage(app, backend, from.as_deref()),
+        Some(Command::Report { from, to, format }) => report::run(app, from, to, format),
+        Some(Command::Export { table, format, ref output, from, to }) => {
+            export::run(app, table, format, output.as_deref(), from, to)
+        }
+        Some(Command::Import { source, ref paths, ref columns, ref tags, dry_run }) => {
+            import::run(app, source, paths, columns, tags, dry_run)
+        }
+        Some(Command::Status { json }) => commands::run(Request::Status, json, app),
+        Some(Command::Start { json }) => {
+            let req = Request::Start { work: args.work, rest: args.rest, long_break: args.long_break };
+            commands::run(req, json, app)
+        }
+        Some(Command::Pause { json }) => commands::run(Request::Pause, json, app),
+        Some(Command::Resume { json }) => commands::run(Request::Resume, json, app),
+        Some(Command::Skip { json }) => commands::run(Request::Skip, json, app),
+        Some(Command::Reset { json }) => commands::run(Request::Reset, json, app),
+    }
+}
+
+fn run_tui(mut app: AppState) -> Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -1535,13 +3636,13 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut AppState
         terminal.draw(|f| render_ui(f, app))?;
 
         let timeout = TICK_RATE.saturating_sub(last_tick.elapsed());
-        if event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                if handle_input(key, app) {
-                    app.save_on_quit();
-                    return Ok(());
-                }
-            }
+        if event::poll(timeout)?
+            && let Event::Key(key) = event::read()?
+            && handle_input(key, app)
+        {
+            let stopped = app.record_quit();
+            app.save_on_quit();
+            return stopped.map_err(|e| format!("Timewarrior: {}", e).into());
         }
 
         if last_tick.elapsed() >= TICK_RATE {
@@ -1550,3 +3651,411 @@ fn run(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut AppState
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{record, session, stats};
+    use serde_json::json;
+
+    fn secs(n: u64) -> Duration {
+        Duration::from_secs(n)
+    }
+
+    #[test]
+    fn clock_counts_wall_time() {
+        let t0 = SystemTime::UNIX_EPOCH + secs(1_000_000);
+        let clock = PhaseClock::start(t0, Duration::ZERO, false);
+
+        assert_eq!(clock.elapsed(t0 + secs(90)), secs(90));
+        assert_eq!(clock.end_time(secs(25 * 60)), Some(t0 + secs(25 * 60)));
+    }
+
+    #[test]
+    fn clock_carries_over_elapsed_time() {
+        let t0 = SystemTime::UNIX_EPOCH + secs(1_000_000);
+        let clock = PhaseClock::start(t0, secs(600), false);
+
+        assert_eq!(clock.elapsed(t0), secs(600));
+        assert_eq!(clock.end_time(secs(1500)), Some(t0 + secs(900)));
+    }
+
+    #[test]
+    fn clock_stops_while_paused() {
+        let t0 = SystemTime::UNIX_EPOCH + secs(1_000_000);
+        let mut clock = PhaseClock::start(t0, Duration::ZERO, false);
+
+        clock.pause(t0 + secs(100));
+        assert!(clock.is_paused());
+        assert_eq!(clock.elapsed(t0 + secs(400)), secs(100));
+        assert_eq!(clock.paused(t0 + secs(400)), secs(300));
+        assert_eq!(clock.end_time(secs(1500)), None);
+
+        // A second pause doesn't move the start of the first.
+        clock.pause(t0 + secs(200));
+        clock.resume(t0 + secs(400));
+        assert!(!clock.is_paused());
+        assert_eq!(clock.elapsed(t0 + secs(500)), secs(200));
+        assert_eq!(clock.paused(t0 + secs(500)), secs(300));
+        assert_eq!(clock.end_time(secs(1500)), Some(t0 + secs(1800)));
+    }
+
+    #[test]
+    fn clock_starts_paused() {
+        let t0 = SystemTime::UNIX_EPOCH + secs(1_000_000);
+        let mut clock = PhaseClock::start(t0, secs(60), true);
+
+        assert_eq!(clock.elapsed(t0 + secs(600)), secs(60));
+        clock.resume(t0 + secs(600));
+        assert_eq!(clock.elapsed(t0 + secs(660)), secs(120));
+    }
+
+    #[test]
+    fn clock_survives_saving() {
+        let t0 = SystemTime::UNIX_EPOCH + secs(1_000_000);
+        let mut clock = PhaseClock::start(t0, secs(30), false);
+        clock.pause(t0 + secs(100));
+        clock.resume(t0 + secs(160));
+        clock.pause(t0 + secs(200));
+
+        let json = serde_json::to_string(&clock.save()).unwrap();
+        let restored = PhaseClock::restore(&serde_json::from_str(&json).unwrap()).unwrap();
+        assert!(restored.is_paused());
+        assert_eq!(restored.elapsed(t0 + secs(900)), clock.elapsed(t0 + secs(900)));
+        assert_eq!(restored.paused(t0 + secs(900)), clock.paused(t0 + secs(900)));
+    }
+
+    #[test]
+    fn clock_discarded_pause_counts() {
+        let t0 = SystemTime::UNIX_EPOCH + secs(1_000_000);
+        let mut clock = PhaseClock::start(t0, Duration::ZERO, false);
+
+        clock.pause(t0 + secs(100));
+        clock.discard_pause();
+        assert_eq!(clock.elapsed(t0 + secs(400)), secs(400));
+        assert_eq!(clock.paused(t0 + secs(400)), Duration::ZERO);
+    }
+
+    #[test]
+    fn detached_pause_keeps_its_interruption() {
+        let now = SystemTime::now();
+        let mut app = AppState::new(Config::default(), Statistics::default(), None);
+        app.pause(now);
+
+        let json = serde_json::to_string(&app.timer_state(true)).unwrap();
+        let mut app = AppState::new(Config::default(), Statistics::default(), Some(serde_json::from_str(&json).unwrap()));
+        app.resume(now + secs(90));
+        assert_eq!(app.interruptions.len(), 1);
+        assert_eq!(app.interruptions[0].duration_secs, 90);
+    }
+
+    #[test]
+    fn tags_are_trimmed_lowercased_and_unique() {
+        let tags = parse_tags("Fixed #Parser, then #parser again. #rust/async_io! # #?");
+        assert_eq!(tags, ["parser", "rust/async_io"]);
+        assert!(parse_tags("no tags, just C# and issue#4").is_empty());
+    }
+
+    #[test]
+    fn label_splits_project_from_tags() {
+        let (project, tags) = parse_label("  rtimer #Rust  docs #review #rust ");
+        assert_eq!(project.as_deref(), Some("rtimer docs"));
+        assert_eq!(tags, ["rust", "review"]);
+        assert_eq!(parse_label("#rust"), (None, vec!["rust".to_string()]));
+        assert_eq!(parse_label(""), (None, Vec::new()));
+    }
+
+    #[test]
+    fn task_estimate_follows_a_tilde() {
+        assert_eq!(parse_task("Write report ~3"), ("Write report".to_string(), 3));
+        assert_eq!(parse_task("  Write report  "), ("Write report".to_string(), 1));
+        assert_eq!(parse_task("Write report ~0"), ("Write report".to_string(), 1));
+        assert_eq!(parse_task("Fix ~/.bashrc"), ("Fix ~/.bashrc".to_string(), 1));
+        assert_eq!(parse_task("ver~2"), ("ver~2".to_string(), 1));
+    }
+
+    #[test]
+    fn task_ids_are_not_reused() {
+        let mut app = AppState::new(Config::default(), Statistics::default(), None);
+        let add = |app: &mut AppState, name: &str| {
+            app.handle_request(Request::AddTask { name: name.into(), estimate: 1 });
+            app.stats.tasks.last().unwrap().id
+        };
+        add(&mut app, "first");
+        let top = add(&mut app, "second");
+        app.handle_request(Request::DeleteTask { id: top });
+
+        let json = serde_json::to_string(&app.stats).unwrap();
+        app.stats = serde_json::from_str(&json).unwrap();
+        assert_ne!(add(&mut app, "third"), top);
+    }
+
+    /// A work phase started 27 minutes ago on a machine that went to sleep
+    /// a minute in, updated once after waking up.
+    fn woken_up(policy: SuspendPolicy) -> AppState {
+        let now = SystemTime::now();
+        let config = Config { suspend_policy: policy, ..Config::default() };
+        let mut app = AppState::new(config, Statistics::default(), None);
+        app.clock = PhaseClock::start(now - secs(27 * 60), Duration::ZERO, false);
+        app.last_update = now - secs(26 * 60);
+        app.update();
+        app
+    }
+
+    fn elapsed_mins(app: &AppState) -> u64 {
+        (app.clock.elapsed(SystemTime::now()).as_secs_f64() / 60.0).round() as u64
+    }
+
+    #[test]
+    fn suspend_counted_as_elapsed() {
+        let app = woken_up(SuspendPolicy::CountElapsed);
+        let reasons: Vec<EndReason> = app.stats.session_history.iter().map(|s| s.end_reason).collect();
+        assert!(reasons == [EndReason::Suspended]);
+        assert!(app.phase == Phase::ShortBreak);
+        assert_eq!(elapsed_mins(&app), 2);
+        assert!(app.pending_suspend.is_none());
+    }
+
+    #[test]
+    fn suspend_pauses_where_it_began() {
+        let app = woken_up(SuspendPolicy::Pause);
+        assert!(app.clock.is_paused());
+        assert_eq!(elapsed_mins(&app), 1);
+        assert!(app.stats.session_history.is_empty());
+        assert!(app.pending_suspend.is_none());
+    }
+
+    #[test]
+    fn suspend_asks_and_counts() {
+        let mut app = woken_up(SuspendPolicy::Ask);
+        assert!(app.clock.is_paused());
+        assert_eq!(app.pending_suspend.map(|gap| gap.as_secs() / 60), Some(26));
+
+        app.resolve_suspend(SuspendChoice::Count);
+        assert!(!app.clock.is_paused());
+        app.update();
+        assert!(app.stats.session_history.iter().map(|s| s.end_reason).eq([EndReason::Suspended]));
+        assert_eq!(elapsed_mins(&app), 2);
+    }
+
+    #[test]
+    fn phases_caught_up_without_a_suspend_are_completed() {
+        // A timer left running while no rtimer was around, resumed by a
+        // new one: the update is the first, so there is no gap.
+        let now = SystemTime::now();
+        let mut app = AppState::new(Config::default(), Statistics::default(), None);
+        app.clock = PhaseClock::start(now - secs(27 * 60), Duration::ZERO, false);
+        app.update();
+        assert!(app.stats.session_history.iter().map(|s| s.end_reason).eq([EndReason::Completed]));
+        assert!(app.phase == Phase::ShortBreak);
+    }
+
+    #[test]
+    fn skipped_work_counts_towards_a_break_as_worked() {
+        let now = SystemTime::now();
+        let mut app = AppState::new(Config::default(), Statistics::default(), None);
+        app.clock = PhaseClock::start(now - secs(11 * 60), Duration::ZERO, false);
+        // Skipped a minute ago, so nothing is announced.
+        app.advance_phase(now - secs(60), EndReason::Skipped);
+        assert_eq!((app.work_time_since_break.as_secs_f64() / 60.0).round(), 10.0);
+    }
+
+    #[test]
+    fn suspend_asks_and_discards() {
+        let mut app = woken_up(SuspendPolicy::Ask);
+        app.resolve_suspend(SuspendChoice::Discard);
+        assert!(!app.clock.is_paused());
+        assert_eq!(elapsed_mins(&app), 1);
+        assert!(app.stats.session_history.is_empty());
+        assert!(app.pending_suspend.is_none());
+    }
+
+    #[test]
+    fn suspend_asks_and_stays_paused() {
+        let mut app = woken_up(SuspendPolicy::Ask);
+        app.resolve_suspend(SuspendChoice::StayPaused);
+        assert!(app.clock.is_paused());
+        assert_eq!(elapsed_mins(&app), 1);
+        assert!(app.pending_suspend.is_none());
+    }
+
+    fn date(text: &str) -> chrono::NaiveDate {
+        text.parse().unwrap()
+    }
+
+    /// A completed work session ending at `ended`, 25 minutes after it began.
+    fn work(ended: &str) -> serde_json::Value {
+        let end = chrono::DateTime::parse_from_rfc3339(ended).unwrap();
+        session(&(end - chrono::Duration::minutes(25)).to_rfc3339(), ended, "Work")
+    }
+
+    #[test]
+    fn weeks_start_on_monday() {
+        // 2026-10-04 is a Sunday.
+        assert_eq!(week_start(date("2026-10-04"), 0), date("2026-09-28"));
+        assert_eq!(week_start(date("2026-10-05"), 0), date("2026-10-05"));
+        assert_eq!(week_start(date("2026-10-05"), 1), date("2026-09-28"));
+        assert_eq!(week_start(date("2026-10-11"), 2), date("2026-09-21"));
+    }
+
+    #[test]
+    fn week_totals_split_at_monday() {
+        let stats = stats(json!({ "session_history": [
+            work("2026-10-04T21:00:00+02:00"),
+            work("2026-10-05T08:00:00+02:00"),
+            work("2026-10-05T09:00:00+02:00"),
+        ]}));
+        let last = stats.week_totals(date("2026-09-28"));
+        assert_eq!(last.map(|t| t.work_sessions), [0, 0, 0, 0, 0, 0, 1]);
+        let this = stats.week_totals(date("2026-10-05"));
+        assert_eq!(this.map(|t| t.work_sessions), [2, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(this[0].work_secs, 2 * 1500);
+    }
+
+    #[test]
+    fn sessions_count_on_the_day_they_end() {
+        let stats = stats(json!({ "session_history": [work("2026-10-02T00:15:00+02:00")] }));
+        let days = stats.daily_totals(date("2026-10-01"), date("2026-10-02"));
+        assert_eq!(days.keys().copied().collect::<Vec<_>>(), [date("2026-10-02")]);
+        assert_eq!(stats.week_totals(date("2026-09-28"))[4].work_sessions, 1);
+    }
+
+    #[test]
+    fn empty_days_have_no_totals() {
+        let stats = stats(json!({ "session_history": [
+            work("2026-10-01T10:00:00+02:00"),
+            work("2026-10-05T10:00:00+02:00"),
+        ]}));
+        let days = stats.daily_totals(date("2026-10-01"), date("2026-10-05"));
+        assert_eq!(days.keys().copied().collect::<Vec<_>>(), [date("2026-10-01"), date("2026-10-05")]);
+        assert_eq!(stats.daily_totals(date("2026-10-02"), date("2026-10-04")).len(), 0);
+        assert_eq!(stats.day_totals(date("2026-10-03")), Totals::default());
+
+        let week = stats.week_totals(date("2026-09-28"));
+        assert_eq!(week.map(|t| t.work_sessions), [0, 0, 0, 1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn goal_totals_cover_today_and_the_iso_week() {
+        let stats = stats(json!({ "session_history": [
+            work("2026-10-04T10:00:00+02:00"),
+            work("2026-10-05T10:00:00+02:00"),
+            work("2026-10-07T09:00:00+02:00"),
+            work("2026-10-07T10:00:00+02:00"),
+        ]}));
+        let (today, week) = stats.goal_totals(date("2026-10-07"));
+        assert_eq!(today.work_sessions, 2);
+        assert_eq!(week.work_sessions, 3);
+        // On a Monday the week is that day alone.
+        assert_eq!(stats.goal_totals(date("2026-10-05")).1.work_sessions, 1);
+    }
+
+    /// Two sessions on 1 October, one on the 2nd, none on the 3rd, two on
+    /// Sunday the 4th and one on Monday the 5th.
+    fn goal_history() -> Statistics {
+        stats(json!({ "session_history": [
+            work("2026-10-01T09:00:00+02:00"),
+            work("2026-10-01T10:00:00+02:00"),
+            work("2026-10-02T09:00:00+02:00"),
+            work("2026-10-04T09:00:00+02:00"),
+            work("2026-10-04T10:00:00+02:00"),
+            work("2026-10-05T09:00:00+02:00"),
+        ]}))
+    }
+
+    #[test]
+    fn daily_goal_hits() {
+        let stats = goal_history();
+        let met = |t: &Totals| t.work_sessions >= 2;
+        // The 3rd had no sessions and counts as missed; today isn't met yet.
+        assert_eq!(stats.goal_hits(date("2026-10-05"), |d| d, met), (2, 4));
+
+        let mut stats = stats;
+        stats.session_history.push(record(work("2026-10-05T10:00:00+02:00")));
+        assert_eq!(stats.goal_hits(date("2026-10-05"), |d| d, met), (3, 5));
+        assert_eq!(Statistics::default().goal_hits(date("2026-10-05"), |d| d, met), (0, 0));
+    }
+
+    #[test]
+    fn weekly_goal_hits() {
+        let stats = goal_history();
+        let monday = |d: chrono::NaiveDate| d.week(chrono::Weekday::Mon).first_day();
+        // Sunday's sessions are needed to reach five in the week of 28 September.
+        let met = |t: &Totals| t.work_sessions >= 5;
+        assert_eq!(stats.goal_hits(date("2026-10-05"), monday, met), (1, 1));
+        assert_eq!(stats.goal_hits(date("2026-10-04"), monday, met), (1, 1));
+        // Two weeks later, the week of the 5th and the empty one after it are missed.
+        assert_eq!(stats.goal_hits(date("2026-10-19"), monday, met), (1, 3));
+    }
+
+    #[test]
+    fn streak_survives_an_unfinished_today() {
+        // Sessions on the 1st, 2nd and 3rd, none yet on the 4th.
+        let stats = stats(json!({ "session_history": [
+            work("2026-10-01T10:00:00+02:00"),
+            work("2026-10-02T10:00:00+02:00"),
+            work("2026-10-03T10:00:00+02:00"),
+        ]}));
+        assert_eq!(stats.streaks(date("2026-10-03"), 1), (3, 3));
+        assert_eq!(stats.streaks(date("2026-10-04"), 1), (3, 3));
+        assert_eq!(stats.streaks(date("2026-10-05"), 1), (0, 3));
+    }
+
+    #[test]
+    fn streak_resets_after_a_gap() {
+        let stats = stats(json!({ "session_history": [
+            work("2026-10-01T10:00:00+02:00"),
+            work("2026-10-02T10:00:00+02:00"),
+            work("2026-10-03T10:00:00+02:00"),
+            work("2026-10-05T10:00:00+02:00"),
+            work("2026-10-06T10:00:00+02:00"),
+        ]}));
+        // The longest run is the one before the gap on the 4th.
+        assert_eq!(stats.streaks(date("2026-10-06"), 1), (2, 3));
+        assert_eq!(stats.streaks(date("2026-10-04"), 1), (3, 3));
+        assert_eq!(Statistics::default().streaks(date("2026-10-06"), 1), (0, 0));
+    }
+
+    #[test]
+    fn streak_needs_the_minimum_sessions() {
+        let stats = stats(json!({ "session_history": [
+            work("2026-10-01T09:00:00+02:00"),
+            work("2026-10-01T10:00:00+02:00"),
+            work("2026-10-02T10:00:00+02:00"),
+            work("2026-10-03T09:00:00+02:00"),
+            work("2026-10-03T10:00:00+02:00"),
+        ]}));
+        assert_eq!(stats.streaks(date("2026-10-03"), 2), (1, 1));
+        assert_eq!(stats.streaks(date("2026-10-03"), 3), (0, 0));
+        // A minimum of 0 still needs a session, so the empty 4th ends the run.
+        assert_eq!(stats.streaks(date("2026-10-03"), 0), (3, 3));
+        assert_eq!(stats.streaks(date("2026-10-05"), 0), (0, 3));
+    }
+
+    #[test]
+    fn rhythm_buckets_by_start_hour_and_weekday() {
+        let mut skipped = session("2026-10-01T09:40:00+02:00", "2026-10-01T09:45:00+02:00", "Work");
+        skipped["end_reason"] = json!("skipped");
+        let stats = stats(json!({ "session_history": [
+            // Started on Wednesday at 23:50, ended on Thursday.
+            session("2026-09-30T23:50:00+02:00", "2026-10-01T00:15:00+02:00", "Work"),
+            session("2026-10-01T09:00:00+02:00", "2026-10-01T09:25:00+02:00", "Work"),
+            session("2026-10-01T09:25:00+02:00", "2026-10-01T09:30:00+02:00", "Short Break"),
+            skipped,
+            session("2026-10-05T09:10:00+02:00", "2026-10-05T09:35:00+02:00", "Work"),
+        ]}));
+
+        let (hours, weekdays) = stats.rhythm(None);
+        assert_eq!((hours[23].completed, hours[23].skipped), (1, 0));
+        assert_eq!((hours[9].completed, hours[9].skipped), (2, 1));
+        assert_eq!(hours.iter().map(|h| h.completed + h.skipped).sum::<u32>(), 4);
+        assert_eq!(weekdays.map(|d| d.completed), [1, 0, 1, 1, 0, 0, 0]);
+        assert_eq!(weekdays[3].skipped, 1);
+        assert_eq!(weekdays[3].rate(), Some(0.5));
+        assert_eq!(weekdays[4].rate(), None);
+
+        let (hours, weekdays) = stats.rhythm(Some(date("2026-10-02")));
+        assert_eq!(hours[9].completed, 1);
+        assert_eq!(weekdays.map(|d| d.completed), [1, 0, 0, 0, 0, 0, 0]);
+    }
+}
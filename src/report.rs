@@ -0,0 +1,230 @@
+use crate::commands::latest_stats;
+use crate::{format_secs, AppState, Completion, Note, Result, Statistics, Totals, DAILY_FMT};
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::{fmt::Write, io::{self, Write as _}};
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub(crate) enum ReportFormat {
+    /// Markdown with a per-day table
+    Md,
+    /// A standalone HTML page
+    Html,
+    /// One JSON object
+    Json,
+}
+
+/// Everything a report shows; also what `--format json` prints.
+#[derive(Serialize)]
+struct Report<'a> {
+    from: String,
+    to: String,
+    #[serde(flatten)]
+    totals: Totals,
+    skipped_sessions: u32,
+    completion_rate: Option<f64>,
+    days: Vec<Day>,
+    notes: Vec<&'a Note>,
+}
+
+#[derive(Serialize)]
+struct Day {
+    date: String,
+    #[serde(skip)]
+    weekday: chrono::Weekday,
+    #[serde(flatten)]
+    totals: Totals,
+}
+
+/// Prints a summary of the days from `from` to `to`, inclusive.
+pub(crate) fn run(app: AppState, from: Option<NaiveDate>, to: Option<NaiveDate>, format: ReportFormat) -> Result<()> {
+    let to = to.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let from = from.unwrap_or(to - chrono::Duration::days(6));
+    if from > to {
+        return Err(format!("--from {} is after --to {}", from, to).into());
+    }
+    let stats = latest_stats(app.stats)?;
+    let report = report(&stats, from, to);
+    let out = match format {
+        ReportFormat::Md => markdown(&report),
+        ReportFormat::Html => html(&report),
+        ReportFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+    };
+    io::stdout().write_all(out.as_bytes())?;
+    Ok(())
+}
+
+fn report(stats: &Statistics, from: NaiveDate, to: NaiveDate) -> Report<'_> {
+    let daily = stats.daily_totals(from, to);
+    let mut totals = Totals::default();
+    let days = from.iter_days().take_while(|d| *d <= to).map(|date| {
+        let day = daily.get(&date).copied().unwrap_or_default();
+        totals += day;
+        Day { date: date.format(DAILY_FMT).to_string(), weekday: date.weekday(), totals: day }
+    }).collect();
+
+    let mut completion = Completion::default();
+    for s in stats.session_history.iter().filter(|s| s.is_work() && s.date().is_some_and(|d| (from..=to).contains(&d))) {
+        completion.add(s);
+    }
+    let notes = stats.notes.iter().filter(|n| {
+        chrono::DateTime::parse_from_rfc3339(&n.timestamp).is_ok_and(|t| (from..=to).contains(&t.date_naive()))
+    }).collect();
+
+    Report {
+        from: from.format(DAILY_FMT).to_string(),
+        to: to.format(DAILY_FMT).to_string(),
+        totals,
+        skipped_sessions: completion.skipped,
+        completion_rate: completion.rate(),
+        days,
+        notes,
+    }
+}
+
+fn completion_text(report: &Report) -> String {
+    match report.completion_rate {
+        Some(rate) => format!(
+            "{:.0}% ({} completed, {} skipped)",
+            rate * 100.0, report.totals.work_sessions, report.skipped_sessions,
+        ),
+        None => "no sessions".into(),
+    }
+}
+
+fn note_time(note: &Note) -> String {
+    chrono::DateTime::parse_from_rfc3339(&note.timestamp)
+        .map_or_else(|_| note.timestamp.clone(), |t| t.format("%Y-%m-%d %H:%M").to_string())
+}
+
+fn markdown(report: &Report) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# Focus report, {} to {}\n", report.from, report.to);
+    let _ = writeln!(out, "- **Focus time:** {}", format_secs(report.totals.work_secs));
+    let _ = writeln!(out, "- **Sessions completed:** {}", report.totals.work_sessions);
+    let _ = writeln!(out, "- **Completion rate:** {}", completion_text(report));
+    let _ = writeln!(out, "- **Break time:** {}", format_secs(report.totals.break_secs));
+
+    let _ = writeln!(out, "\n## Per day\n");
+    let _ = writeln!(out, "| Date | Sessions | Focus | Breaks |");
+    let _ = writeln!(out, "|------|---------:|------:|-------:|");
+    for day in &report.days {
+        let _ = writeln!(
+            out, "| {} {} | {} | {} | {} |",
+            day.date, day.weekday, day.totals.work_sessions,
+            format_secs(day.totals.work_secs), format_secs(day.totals.break_secs),
+        );
+    }
+
+    if !report.notes.is_empty() {
+        let _ = writeln!(out, "\n## Notes\n");
+        for note in &report.notes {
+            let _ = writeln!(out, "- {} ({}): {}", note_time(note), escape_markdown(&note.phase), escape_markdown(&note.content.replace('\n', " ")));
+        }
+    }
+    out
+}
+
+/// Backslash-escapes what Markdown would otherwise read as markup.
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '&' | '|') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn html(report: &Report) -> String {
+    let mut out = String::new();
+    let title = format!("Focus report, {} to {}", report.from, report.to);
+    let _ = writeln!(out, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>{}</title>", title);
+    let _ = writeln!(
+        out,
+        "<style>body{{font-family:sans-serif;max-width:48em;margin:2em auto}}\
+         table{{border-collapse:collapse}}td,th{{padding:.2em .8em;border-bottom:1px solid #ddd}}\
+         td.n{{text-align:right}}</style>\n</head>\n<body>",
+    );
+    let _ = writeln!(out, "<h1>{}</h1>\n<ul>", title);
+    let _ = writeln!(out, "<li><strong>Focus time:</strong> {}</li>", format_secs(report.totals.work_secs));
+    let _ = writeln!(out, "<li><strong>Sessions completed:</strong> {}</li>", report.totals.work_sessions);
+    let _ = writeln!(out, "<li><strong>Completion rate:</strong> {}</li>", completion_text(report));
+    let _ = writeln!(out, "<li><strong>Break time:</strong> {}</li>\n</ul>", format_secs(report.totals.break_secs));
+
+    let _ = writeln!(out, "<h2>Per day</h2>\n<table>\n<tr><th>Date</th><th>Sessions</th><th>Focus</th><th>Breaks</th></tr>");
+    for day in &report.days {
+        let _ = writeln!(
+            out, "<tr><td>{} {}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td></tr>",
+            day.date, day.weekday, day.totals.work_sessions,
+            format_secs(day.totals.work_secs), format_secs(day.totals.break_secs),
+        );
+    }
+    let _ = writeln!(out, "</table>");
+
+    if !report.notes.is_empty() {
+        let _ = writeln!(out, "<h2>Notes</h2>\n<ul>");
+        for note in &report.notes {
+            let _ = writeln!(out, "<li>{} ({}): {}</li>", note_time(note), escape(&note.phase), escape(&note.content));
+        }
+        let _ = writeln!(out, "</ul>");
+    }
+    let _ = writeln!(out, "</body>\n</html>");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{record, sample, session};
+
+    /// The shared sample plus a work session skipped on 2 October and a
+    /// note full of markup characters.
+    fn stats() -> Statistics {
+        let mut stats = sample();
+        let mut skipped = record(session("2026-10-02T14:00:00+02:00", "2026-10-02T14:10:00+02:00", "Work"));
+        skipped.end_reason = crate::EndReason::Skipped;
+        skipped.elapsed_secs = 600;
+        stats.session_history.push(skipped);
+        let mut note = stats.notes[0].clone();
+        note.timestamp = "2026-10-02T14:05:00+02:00".into();
+        note.content = "<b>a & b</b> | c".into();
+        stats.notes.push(note);
+        stats
+    }
+
+    #[test]
+    fn totals_cover_the_range() {
+        let stats = stats();
+        let all = report(&stats, "2026-10-01".parse().unwrap(), "2026-10-03".parse().unwrap());
+        assert_eq!(all.totals, Totals { work_sessions: 1, work_secs: 2100, break_secs: 60 });
+        assert_eq!(all.days.len(), 3);
+        assert_eq!(all.days[2].totals, Totals::default());
+        assert_eq!(all.notes.len(), 2);
+        assert_eq!(completion_text(&all), "50% (1 completed, 1 skipped)");
+
+        let second = report(&stats, "2026-10-02".parse().unwrap(), "2026-10-02".parse().unwrap());
+        assert_eq!(second.totals, Totals { work_sessions: 0, work_secs: 600, break_secs: 0 });
+        assert_eq!(completion_text(&second), "0% (0 completed, 1 skipped)");
+        assert_eq!(second.notes.len(), 1);
+
+        let third = report(&stats, "2026-10-03".parse().unwrap(), "2026-10-03".parse().unwrap());
+        assert_eq!(completion_text(&third), "no sessions");
+    }
+
+    #[test]
+    fn notes_are_escaped() {
+        let stats = stats();
+        let report = report(&stats, "2026-10-02".parse().unwrap(), "2026-10-02".parse().unwrap());
+        assert!(markdown(&report).contains(r"(work): \<b\>a \& b\</b\> \| c"));
+        let html = html(&report);
+        assert!(html.contains("(work): &lt;b&gt;a &amp; b&lt;/b&gt; | c</li>"));
+        assert!(!html.contains("<b>"));
+    }
+}
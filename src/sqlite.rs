@@ -0,0 +1,484 @@
+use crate::storage::{self, Storage};
+use crate::{parse_tags, EndReason, Interruption, InterruptionKind, Note, Result, SessionRecord, Statistics, Task, Totals};
+use chrono::DateTime;
+use rusqlite::{backup::Progress, params, types::Type, Connection, DatabaseName, OptionalExtension, Row};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Schema steps, applied in order and tracked with `PRAGMA user_version`.
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE sessions (
+         id          INTEGER PRIMARY KEY,
+         timestamp   TEXT NOT NULL,
+         phase_type  TEXT NOT NULL,
+         duration    INTEGER NOT NULL,
+         completed   INTEGER NOT NULL,
+         UNIQUE (timestamp, phase_type)
+     );
+     CREATE TABLE notes (
+         position    INTEGER PRIMARY KEY,
+         timestamp   TEXT NOT NULL,
+         content     TEXT NOT NULL,
+         phase       TEXT NOT NULL
+     );
+     CREATE TABLE meta (
+         key         TEXT PRIMARY KEY,
+         value       TEXT NOT NULL
+     );",
+    // Counters are replaced by totals computed from the sessions table; what
+    // the old counters covered beyond the stored sessions is archived.
+    "ALTER TABLE sessions ADD COLUMN elapsed_secs INTEGER NOT NULL DEFAULT 0;
+     UPDATE sessions SET elapsed_secs = duration * 60 WHERE completed;
+     INSERT OR REPLACE INTO meta (key, value) SELECT 'archived', json_object(
+         'work_sessions', max(0, coalesce((SELECT CAST(value AS INTEGER) FROM meta WHERE key = 'total_sessions'), 0)
+             - (SELECT count(*) FROM sessions WHERE phase_type = 'Work' AND completed)),
+         'work_secs', max(0, coalesce((SELECT CAST(value AS INTEGER) FROM meta WHERE key = 'total_work_time'), 0) * 60
+             - (SELECT coalesce(sum(elapsed_secs), 0) FROM sessions WHERE phase_type = 'Work')),
+         'break_secs', max(0, coalesce((SELECT CAST(value AS INTEGER) FROM meta WHERE key = 'total_break_time'), 0) * 60
+             - (SELECT coalesce(sum(elapsed_secs), 0) FROM sessions WHERE phase_type <> 'Work'))
+     );
+     DELETE FROM meta WHERE key IN
+         ('total_sessions', 'total_work_time', 'total_break_time', 'sessions_today', 'last_session_date', 'weekly_sessions');",
+    // Start times, pauses and why each phase ended; `completed` becomes an end reason.
+    "ALTER TABLE sessions ADD COLUMN started TEXT;
+     ALTER TABLE sessions ADD COLUMN paused_secs INTEGER NOT NULL DEFAULT 0;
+     ALTER TABLE sessions ADD COLUMN end_reason TEXT NOT NULL DEFAULT 'completed';
+     UPDATE sessions SET end_reason = 'skipped' WHERE NOT completed;
+     ALTER TABLE sessions DROP COLUMN completed;",
+    "CREATE TABLE interruptions (
+         id            INTEGER PRIMARY KEY,
+         session_id    INTEGER NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+         timestamp     TEXT NOT NULL,
+         duration_secs INTEGER NOT NULL,
+         kind          TEXT,
+         reason        TEXT NOT NULL DEFAULT ''
+     );
+     CREATE INDEX interruptions_by_session ON interruptions (session_id);",
+    "CREATE TABLE tasks (
+         id          INTEGER PRIMARY KEY,
+         name        TEXT NOT NULL,
+         estimate    INTEGER NOT NULL,
+         done        INTEGER NOT NULL,
+         created     TEXT NOT NULL
+     );
+     ALTER TABLE sessions ADD COLUMN task INTEGER;",
+    // Note tags are parsed from the note text on load, so only sessions need a table.
+    "ALTER TABLE sessions ADD COLUMN project TEXT;
+     ALTER TABLE notes ADD COLUMN project TEXT;
+     CREATE TABLE tags (
+         session_id  INTEGER NOT NULL REFERENCES sessions (id) ON DELETE CASCADE,
+         tag         TEXT NOT NULL,
+         PRIMARY KEY (session_id, tag)
+     );
+     CREATE INDEX tags_by_tag ON tags (tag);",
+];
+
+/// Keeps the complete session history in `stats.db`. Sessions are only ever
+/// appended, so each save inserts just the records added since the last one.
+pub(crate) struct SqliteStorage {
+    conn: Connection,
+    persisted_sessions: usize,
+    /// Set when `conn` is an upgraded copy of an older database, opened
+    /// without the data directory lock; the file is upgraded on first save.
+    upgrade: Option<PathBuf>,
+}
+
+impl SqliteStorage {
+    /// Opens the database, upgrading it to the current schema. Without the
+    /// data directory lock an older database is left as it is and upgraded
+    /// in a copy in memory instead.
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let version: usize = Connection::open(path)?.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA.len() {
+            return Err(format!(
+                "{} was written by a newer rtimer (database version {})",
+                path.display(), version
+            ).into());
+        }
+        if version < SCHEMA.len() && !storage::holds_lock() {
+            let mut copy = Connection::open_in_memory()?;
+            copy.restore(DatabaseName::Main, path, None::<fn(Progress)>)?;
+            upgrade(&mut copy, version)?;
+            return Ok(Self { conn: copy, persisted_sessions: 0, upgrade: Some(path.to_path_buf()) });
+        }
+        Ok(Self { conn: open_file(path)?, persisted_sessions: 0, upgrade: None })
+    }
+
+    fn meta<T: std::str::FromStr>(&self, key: &str) -> Result<Option<T>> {
+        let value: Option<String> = self.conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| row.get(0))
+            .optional()?;
+        Ok(value.and_then(|v| v.parse().ok()))
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load(&mut self) -> Result<Statistics> {
+        let mut stats = Statistics::default();
+        if let Some(v) = self.meta::<String>("archived")? {
+            stats.archived = serde_json::from_str::<Totals>(&v)?;
+        }
+        stats.next_task_id = self.meta("next_task_id")?.unwrap_or(0);
+        stats.active_task = self.meta("active_task")?;
+        stats.active_project = self.meta("active_project")?;
+        stats.streak_warned = self.meta("streak_warned")?;
+        if let Some(v) = self.meta::<String>("active_tags")? {
+            stats.active_tags = serde_json::from_str(&v)?;
+        }
+
+        let mut query = self.conn.prepare(
+            "SELECT id, started, timestamp, phase_type, duration, elapsed_secs, paused_secs, end_reason, task, project
+             FROM sessions ORDER BY timestamp, id",
+        )?;
+        let mut index = HashMap::new();
+        for row in query.query_map([], |row| Ok((row.get::<_, i64>(0)?, SessionRecord {
+            started: row.get(1)?,
+            timestamp: row.get(2)?,
+            phase_type: row.get(3)?,
+            duration: row.get(4)?,
+            elapsed_secs: row.get(5)?,
+            paused_secs: row.get(6)?,
+            end_reason: column(row, 7, end_reason)?,
+            interruptions: Vec::new(),
+            task: row.get(8)?,
+            project: row.get(9)?,
+            tags: Vec::new(),
+        })))? {
+            let (id, record) = row?;
+            index.insert(id, stats.session_history.len());
+            stats.session_history.push(record);
+        }
+
+        let mut query = self.conn.prepare(
+            "SELECT session_id, timestamp, duration_secs, kind, reason FROM interruptions ORDER BY id",
+        )?;
+        for row in query.query_map([], |row| Ok((row.get::<_, i64>(0)?, Interruption {
+            timestamp: row.get(1)?,
+            duration_secs: row.get(2)?,
+            kind: match row.get::<_, Option<String>>(3)? {
+                Some(_) => Some(column(row, 3, interruption_kind)?),
+                None => None,
+            },
+            reason: row.get(4)?,
+        })))? {
+            let (session, interruption) = row?;
+            if let Some(&i) = index.get(&session) {
+                stats.session_history[i].interruptions.push(interruption);
+            }
+        }
+
+        let mut query = self.conn.prepare("SELECT session_id, tag FROM tags ORDER BY rowid")?;
+        for row in query.query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)))? {
+            let (session, tag) = row?;
+            if let Some(&i) = index.get(&session) {
+                stats.session_history[i].tags.push(tag);
+            }
+        }
+        // Timestamps carry their UTC offset, so the text order is not the time order.
+        stats.session_history.sort_by_key(|s| DateTime::parse_from_rfc3339(&s.timestamp).ok());
+
+        let mut query = self.conn.prepare(
+            "SELECT id, name, estimate, done, created FROM tasks ORDER BY id",
+        )?;
+        stats.tasks = query
+            .query_map([], |row| Ok(Task {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                estimate: row.get(2)?,
+                done: row.get(3)?,
+                created: row.get(4)?,
+            }))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut query = self.conn.prepare(
+            "SELECT timestamp, content, phase, project FROM notes ORDER BY position",
+        )?;
+        stats.notes = query
+            .query_map([], |row| {
+                let content: String = row.get(1)?;
+                Ok(Note {
+                    timestamp: row.get(0)?,
+                    tags: parse_tags(&content),
+                    content,
+                    phase: row.get(2)?,
+                    project: row.get(3)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+
+        self.persisted_sessions = stats.session_history.len();
+        Ok(stats)
+    }
+
+    fn save(&mut self, stats: &Statistics) -> Result<()> {
+        // Saving happens under the lock, so the file can be upgraded now.
+        if let Some(path) = self.upgrade.take() {
+            self.conn = open_file(&path)?;
+        }
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR IGNORE INTO sessions
+                     (started, timestamp, phase_type, duration, elapsed_secs, paused_secs, end_reason, task, project)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+            let mut insert_tag = tx.prepare_cached("INSERT OR IGNORE INTO tags (session_id, tag) VALUES (?1, ?2)")?;
+            let mut insert_interruption = tx.prepare_cached(
+                "INSERT INTO interruptions (session_id, timestamp, duration_secs, kind, reason)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for s in stats.session_history.iter().skip(self.persisted_sessions) {
+                let inserted = insert.execute(params![
+                    s.started, s.timestamp, s.phase_type, s.duration, s.elapsed_secs, s.paused_secs, s.end_reason.label(), s.task, s.project,
+                ])?;
+                if inserted == 0 {
+                    continue;
+                }
+                let session = tx.last_insert_rowid();
+                for i in &s.interruptions {
+                    insert_interruption.execute(params![
+                        session, i.timestamp, i.duration_secs, i.kind.map(|k| InterruptionKind::label(Some(k))), i.reason,
+                    ])?;
+                }
+                for tag in &s.tags {
+                    insert_tag.execute(params![session, tag])?;
+                }
+            }
+
+            // Notes and tasks can be edited and deleted, and there are few of them.
+            tx.execute("DELETE FROM tasks", [])?;
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO tasks (id, name, estimate, done, created) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for t in &stats.tasks {
+                insert.execute(params![t.id, t.name, t.estimate, t.done, t.created])?;
+            }
+
+            tx.execute("DELETE FROM notes", [])?;
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO notes (position, timestamp, content, phase, project) VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (i, n) in stats.notes.iter().enumerate() {
+                insert.execute(params![i as i64, n.timestamp, n.content, n.phase, n.project])?;
+            }
+
+            tx.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES ('archived', ?1)",
+                [serde_json::to_string(&stats.archived)?],
+            )?;
+            tx.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES ('next_task_id', ?1)",
+                [stats.next_task_id.to_string()],
+            )?;
+            match stats.active_task {
+                Some(id) => tx.execute("INSERT OR REPLACE INTO meta (key, value) VALUES ('active_task', ?1)", [id.to_string()])?,
+                None => tx.execute("DELETE FROM meta WHERE key = 'active_task'", [])?,
+            };
+            match &stats.active_project {
+                Some(project) => tx.execute("INSERT OR REPLACE INTO meta (key, value) VALUES ('active_project', ?1)", [project])?,
+                None => tx.execute("DELETE FROM meta WHERE key = 'active_project'", [])?,
+            };
+            match &stats.streak_warned {
+                Some(day) => tx.execute("INSERT OR REPLACE INTO meta (key, value) VALUES ('streak_warned', ?1)", [day])?,
+                None => tx.execute("DELETE FROM meta WHERE key = 'streak_warned'", [])?,
+            };
+            tx.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES ('active_tags', ?1)",
+                [serde_json::to_string(&stats.active_tags)?],
+            )?;
+        }
+        tx.commit()?;
+
+        self.persisted_sessions = stats.session_history.len();
+        Ok(())
+    }
+}
+
+fn open_file(path: &Path) -> Result<Connection> {
+    let mut conn = Connection::open(path)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.pragma_update(None, "synchronous", "FULL")?;
+    // Off by default in SQLite; needed for the ON DELETE CASCADE clauses.
+    conn.pragma_update(None, "foreign_keys", true)?;
+    let version: usize = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    upgrade(&mut conn, version)?;
+    Ok(conn)
+}
+
+/// Applies the schema steps after `version`.
+fn upgrade(conn: &mut Connection, version: usize) -> Result<()> {
+    let tx = conn.transaction()?;
+    for step in &SCHEMA[version..] {
+        tx.execute_batch(step)?;
+    }
+    tx.pragma_update(None, "user_version", SCHEMA.len())?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Reads a text column written with a `label()`, failing the load on values
+/// this version doesn't know rather than guessing.
+fn column<T>(row: &Row, idx: usize, parse: fn(&str) -> Option<T>) -> rusqlite::Result<T> {
+    let text: String = row.get(idx)?;
+    parse(&text).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(idx, Type::Text, format!("unknown value {:?}", text).into())
+    })
+}
+
+fn end_reason(s: &str) -> Option<EndReason> {
+    match s {
+        "completed" => Some(EndReason::Completed),
+        "skipped" => Some(EndReason::Skipped),
+        "reset" => Some(EndReason::Reset),
+        "quit" => Some(EndReason::Quit),
+        "suspended" => Some(EndReason::Suspended),
+        _ => None,
+    }
+}
+
+fn interruption_kind(s: &str) -> Option<InterruptionKind> {
+    match s {
+        "internal" => Some(InterruptionKind::Internal),
+        "external" => Some(InterruptionKind::External),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{record, sample, session, TempPath};
+
+    #[test]
+    fn round_trip() {
+        let path = TempPath::new("round-trip.db");
+        let stats = sample();
+        SqliteStorage::open(&path).unwrap().save(&stats).unwrap();
+
+        let loaded = SqliteStorage::open(&path).unwrap().load().unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&stats).unwrap());
+    }
+
+    #[test]
+    fn saves_only_new_sessions() {
+        let path = TempPath::new("incremental.db");
+        let mut store = SqliteStorage::open(&path).unwrap();
+        let mut stats = store.load().unwrap();
+        stats.session_history = sample().session_history;
+        store.save(&stats).unwrap();
+        assert_eq!(store.persisted_sessions, 2);
+
+        // Changes to sessions already written are not saved again.
+        stats.session_history[0].elapsed_secs = 1;
+        stats.session_history.push(record(session("2026-10-01T09:28:00+02:00", "2026-10-01T09:53:00+02:00", "Work")));
+        store.save(&stats).unwrap();
+        assert_eq!(store.persisted_sessions, 3);
+
+        let loaded = SqliteStorage::open(&path).unwrap().load().unwrap();
+        assert_eq!(loaded.session_history.len(), 3);
+        assert_eq!(loaded.session_history[0].elapsed_secs, 1500);
+        assert_eq!(loaded.session_history[2].timestamp, "2026-10-01T09:53:00+02:00");
+
+        // A store that hasn't loaded anything writes every session, and
+        // those already present are skipped rather than duplicated.
+        SqliteStorage::open(&path).unwrap().save(&stats).unwrap();
+        assert_eq!(SqliteStorage::open(&path).unwrap().load().unwrap().session_history.len(), 3);
+    }
+
+    #[test]
+    fn loads_sessions_in_time_order() {
+        let path = TempPath::new("offsets.db");
+        let mut stats = Statistics::default();
+        // 08:30 UTC sorts before 09:20+02:00 as text but is an hour after it.
+        let mut utc = record(session("2026-10-01T08:05:00+00:00", "2026-10-01T08:30:00+00:00", "Work"));
+        utc.tags = vec!["travel".to_string()];
+        stats.session_history = vec![
+            utc,
+            record(session("2026-10-01T08:55:00+02:00", "2026-10-01T09:20:00+02:00", "Work")),
+        ];
+        SqliteStorage::open(&path).unwrap().save(&stats).unwrap();
+
+        let loaded = SqliteStorage::open(&path).unwrap().load().unwrap();
+        let times: Vec<&str> = loaded.session_history.iter().map(|s| s.timestamp.as_str()).collect();
+        assert_eq!(times, ["2026-10-01T09:20:00+02:00", "2026-10-01T08:30:00+00:00"]);
+        assert_eq!(loaded.session_history[1].tags, ["travel"]);
+    }
+
+    #[test]
+    fn deleting_a_session_deletes_its_details() {
+        let path = TempPath::new("cascade.db");
+        let mut store = SqliteStorage::open(&path).unwrap();
+        store.save(&sample()).unwrap();
+
+        store.conn.execute("DELETE FROM sessions", []).unwrap();
+        for table in ["interruptions", "tags"] {
+            let rows: i64 = store.conn
+                .query_row(&format!("SELECT count(*) FROM {}", table), [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(rows, 0, "{} left behind", table);
+        }
+    }
+
+    #[test]
+    fn migrates_first_version_database() {
+        let path = TempPath::new("migrate.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(SCHEMA[0]).unwrap();
+            conn.pragma_update(None, "user_version", 1).unwrap();
+            conn.execute_batch(
+                "INSERT INTO sessions (timestamp, phase_type, duration, completed) VALUES
+                     ('2026-10-01T09:25:00+02:00', 'Work', 25, 1),
+                     ('2026-10-01T09:30:00+02:00', 'Short Break', 5, 1),
+                     ('2026-10-01T09:33:00+02:00', 'Work', 25, 0);
+                 INSERT INTO notes (position, timestamp, content, phase) VALUES
+                     (0, '2026-10-01T09:10:00+02:00', 'fix the bug', 'work');
+                 INSERT INTO meta (key, value) VALUES
+                     ('total_sessions', '12'), ('total_work_time', '300'), ('total_break_time', '60'),
+                     ('sessions_today', '2'), ('weekly_sessions', '[1,2,0,0,3,0,2]');",
+            ).unwrap();
+        }
+
+        let file_version = || -> usize {
+            Connection::open(&path).unwrap().pragma_query_value(None, "user_version", |row| row.get(0)).unwrap()
+        };
+        let mut store = SqliteStorage::open(&path).unwrap();
+        let version: usize = store.conn.pragma_query_value(None, "user_version", |row| row.get(0)).unwrap();
+        assert_eq!(version, SCHEMA.len());
+        // Without the data directory lock the file is only upgraded on save.
+        assert_eq!(file_version(), 1);
+
+        let stats = store.load().unwrap();
+        let reasons: Vec<EndReason> = stats.session_history.iter().map(|s| s.end_reason).collect();
+        assert!(reasons == [EndReason::Completed, EndReason::Completed, EndReason::Skipped]);
+        assert_eq!(stats.session_history[0].elapsed_secs, 25 * 60);
+        assert_eq!(stats.session_history[2].elapsed_secs, 0);
+        assert_eq!(stats.notes[0].content, "fix the bug");
+        // The old counters covered 11 sessions beyond the one stored.
+        assert_eq!(stats.archived, Totals { work_sessions: 11, work_secs: 275 * 60, break_secs: 55 * 60 });
+        assert_eq!(stats.totals(), Totals { work_sessions: 12, work_secs: 300 * 60, break_secs: 60 * 60 });
+        assert!(store.meta::<String>("sessions_today").unwrap().is_none());
+
+        store.save(&stats).unwrap();
+        assert_eq!(file_version(), SCHEMA.len());
+        assert_eq!(store.load().unwrap().session_history.len(), 3);
+    }
+
+    #[test]
+    fn refuses_newer_database() {
+        let path = TempPath::new("newer.db");
+        Connection::open(&path).unwrap().pragma_update(None, "user_version", SCHEMA.len() + 1).unwrap();
+        assert!(SqliteStorage::open(&path).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_end_reason() {
+        let path = TempPath::new("bad-reason.db");
+        let mut store = SqliteStorage::open(&path).unwrap();
+        store.save(&sample()).unwrap();
+        store.conn.execute("UPDATE sessions SET end_reason = 'abandoned' WHERE phase_type = 'Work'", []).unwrap();
+        assert!(store.load().is_err());
+    }
+}
@@ -0,0 +1,573 @@
+use crate::Result;
+use crate::{Config, Statistics, StorageBackend, TimerState, Totals};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    fs::{self, File, TryLockError},
+    io::{self, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
+
+// ============================================================================
+// Locations
+// ============================================================================
+//
+// config.json        $XDG_CONFIG_HOME/rtimer  (~/.config/rtimer)
+// timer_state.json   $XDG_STATE_HOME/rtimer   (~/.local/state/rtimer)
+// everything else    $XDG_DATA_HOME/rtimer    (~/.local/share/rtimer)
+//
+// `--data-dir` puts all of them in one directory instead.
+
+const LEGACY_FILES: &[&str] = &["config.json", "stats.json", "timer_state.json", "stats_export.csv"];
+
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+pub(crate) fn set_data_dir(dir: PathBuf) {
+    let _ = DATA_DIR_OVERRIDE.set(dir);
+}
+
+fn xdg_dir(var: &str, home_fallback: &str) -> PathBuf {
+    std::env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(home_fallback)))
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("rtimer")
+}
+
+fn dir_for(filename: &str) -> PathBuf {
+    if let Some(dir) = DATA_DIR_OVERRIDE.get() {
+        return dir.clone();
+    }
+    match filename {
+        "config.json" => xdg_dir("XDG_CONFIG_HOME", ".config"),
+        "timer_state.json" => xdg_dir("XDG_STATE_HOME", ".local/state"),
+        _ => xdg_dir("XDG_DATA_HOME", ".local/share"),
+    }
+}
+
+pub(crate) fn get_path(filename: &str) -> PathBuf {
+    let dir = dir_for(filename);
+    let _ = fs::create_dir_all(&dir);
+    dir.join(filename)
+}
+
+/// Moves files from the old `./rtimer` directory (relative to wherever rtimer
+/// happened to be launched, usually the home directory for the desktop
+/// entry) into the XDG locations. Files that already exist at the new
+/// location are left alone so nothing is overwritten.
+///
+/// This happens once, after which a marker file in the data directory skips
+/// it. `quiet` runs (one-shot commands whose output scripts read) report
+/// nothing, and leave files they could not move for the next interactive
+/// run to report.
+pub(crate) fn migrate_legacy_dirs(quiet: bool) {
+    let marker = get_path("legacy_migrated");
+    if marker.exists() {
+        return;
+    }
+    let mut candidates = vec![PathBuf::from("rtimer")];
+    if let Some(home) = std::env::var_os("HOME") {
+        candidates.push(Path::new(&home).join("rtimer"));
+    }
+
+    let mut seen = Vec::new();
+    let mut messages = Vec::new();
+    let mut done = true;
+    for dir in candidates {
+        let Ok(dir) = dir.canonicalize() else { continue };
+        if !dir.is_dir() || seen.contains(&dir) {
+            continue;
+        }
+        seen.push(dir.clone());
+        done &= migrate_legacy_dir(&dir, &mut messages);
+    }
+
+    if !quiet {
+        for message in &messages {
+            eprintln!("rtimer: {}", message);
+        }
+    }
+    if done || !quiet {
+        let _ = fs::write(&marker, "");
+    }
+}
+
+/// Returns whether every file was moved.
+fn migrate_legacy_dir(dir: &Path, messages: &mut Vec<String>) -> bool {
+    let mut done = true;
+    for name in LEGACY_FILES {
+        let from = dir.join(name);
+        if !from.is_file() {
+            continue;
+        }
+        let to = get_path(name);
+        if to.canonicalize().is_ok_and(|to| to == from) {
+            continue;
+        }
+        if to.exists() {
+            messages.push(format!("not migrating {} ({} already exists)", from.display(), to.display()));
+            done = false;
+            continue;
+        }
+        let moved = fs::rename(&from, &to)
+            .or_else(|_| fs::copy(&from, &to).and_then(|_| fs::remove_file(&from)));
+        match moved {
+            Ok(()) => messages.push(format!("moved {} to {}", from.display(), to.display())),
+            Err(e) => {
+                messages.push(format!("could not move {}: {}", from.display(), e));
+                done = false;
+            }
+        }
+    }
+
+    // Only succeeds once everything has been moved out.
+    let _ = fs::remove_dir(dir);
+    done
+}
+
+// ============================================================================
+// JSON Files
+// ============================================================================
+
+/// Loads a versioned JSON file, returning `None` if it does not exist.
+///
+/// Older layouts are upgraded through the type's migration chain. While this
+/// process holds the data directory lock, the upgrade is also written back
+/// in place (after keeping a `.v<N>.bak` copy). A file that cannot be
+/// parsed is never silently replaced: it is copied to a timestamped
+/// `.corrupt` backup, the parse error is reported, and the user is asked
+/// whether to start fresh. Without a terminal to ask on, loading fails.
+pub(crate) fn load_json<T: Versioned>(path: &Path) -> Result<Option<T>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("could not read {}: {}", path.display(), e).into()),
+    };
+
+    let value = match serde_json::from_str::<Value>(&text) {
+        Ok(value) => value,
+        Err(e) => return recover_corrupt(path, &e).map(|_| Some(T::default())),
+    };
+    let from = schema_version(&value);
+    if from > T::VERSION {
+        return Err(format!(
+            "{} was written by a newer rtimer (schema version {}, this build understands up to {})",
+            path.display(), from, T::VERSION
+        ).into());
+    }
+
+    let data = match migrate::<T>(value) {
+        Ok(data) => data,
+        Err(e) => return recover_corrupt(path, &e).map(|_| Some(T::default())),
+    };
+    if from < T::VERSION && holds_lock() {
+        let mut backup_name = path.file_name().unwrap_or_default().to_os_string();
+        backup_name.push(format!(".v{}.bak", from));
+        fs::copy(path, path.with_file_name(backup_name))?;
+        save_json(path, &data)?;
+    }
+    Ok(Some(data))
+}
+
+fn recover_corrupt(path: &Path, err: &serde_json::Error) -> Result<()> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let mut backup_name = path.file_name().unwrap_or_default().to_os_string();
+    backup_name.push(format!(".{}.corrupt", stamp));
+    let backup = path.with_file_name(backup_name);
+    fs::copy(path, &backup)?;
+
+    eprintln!("rtimer: {} could not be read: {}", path.display(), err);
+    eprintln!("rtimer: a copy of the broken file was saved as {}", backup.display());
+
+    if !io::stdin().is_terminal() {
+        return Err(format!(
+            "refusing to overwrite {}; fix or remove it and start rtimer again",
+            path.display()
+        ).into());
+    }
+
+    eprint!("Start fresh and replace it on the next save? [y/N] ");
+    io::stderr().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    if matches!(answer.trim(), "y" | "Y" | "yes") {
+        Ok(())
+    } else {
+        Err(format!("aborted; {} was left untouched", path.display()).into())
+    }
+}
+
+pub(crate) fn save_json<T: Serialize>(path: &Path, data: &T) -> io::Result<()> {
+    write_atomic(path, serde_json::to_string_pretty(data)?.as_bytes())
+}
+
+/// Writes through a temp file in the same directory, fsyncs it and renames it
+/// over `path`, so a crash leaves either the old or the new file but never a
+/// truncated one.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(path.file_name().unwrap_or_default());
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp = path.with_file_name(tmp_name);
+
+    let written = File::create(&tmp).and_then(|mut f| {
+        f.write_all(contents)?;
+        f.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+
+    // Persist the rename itself.
+    if let Some(dir) = path.parent()
+        && let Ok(dir) = File::open(dir)
+    {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Statistics Backends
+// ============================================================================
+
+/// Where statistics, session history and notes are persisted. Config and
+/// timer state always live in their own JSON files.
+pub(crate) trait Storage {
+    fn load(&mut self) -> Result<Statistics>;
+    fn save(&mut self, stats: &Statistics) -> Result<()>;
+}
+
+pub(crate) fn open_store(backend: StorageBackend) -> Result<Box<dyn Storage>> {
+    match backend {
+        StorageBackend::Json => Ok(Box::new(JsonStorage { path: get_path(backend.file_name()) })),
+        #[cfg(feature = "sqlite")]
+        StorageBackend::Sqlite => Ok(Box::new(crate::sqlite::SqliteStorage::open(&get_path(backend.file_name()))?)),
+        #[cfg(not(feature = "sqlite"))]
+        StorageBackend::Sqlite => Err("this rtimer was built without SQLite support (feature `sqlite`)".into()),
+    }
+}
+
+/// The original single-file backend.
+pub(crate) struct JsonStorage {
+    pub(crate) path: PathBuf,
+}
+
+impl Storage for JsonStorage {
+    fn load(&mut self) -> Result<Statistics> {
+        Ok(load_json::<Statistics>(&self.path)?.unwrap_or_default())
+    }
+
+    fn save(&mut self, stats: &Statistics) -> Result<()> {
+        Ok(save_json(&self.path, stats)?)
+    }
+}
+
+// ============================================================================
+// Locking
+// ============================================================================
+
+/// Advisory lock on the data directory, held for as long as the value lives.
+pub(crate) struct DataLock {
+    _file: File,
+}
+
+static LOCK_HELD: AtomicBool = AtomicBool::new(false);
+
+impl Drop for DataLock {
+    fn drop(&mut self) {
+        LOCK_HELD.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Whether this process holds the data directory lock, and so may write
+/// upgraded files back.
+pub(crate) fn holds_lock() -> bool {
+    LOCK_HELD.load(Ordering::SeqCst)
+}
+
+/// Takes the data directory lock, or returns `None` if another rtimer
+/// process already holds it.
+pub(crate) fn lock_data_dir() -> io::Result<Option<DataLock>> {
+    let mut file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(get_path("rtimer.lock"))?;
+
+    match file.try_lock() {
+        Ok(()) => {
+            file.set_len(0)?;
+            write!(file, "{}", std::process::id())?;
+            LOCK_HELD.store(true, Ordering::SeqCst);
+            Ok(Some(DataLock { _file: file }))
+        }
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(e)) => Err(e),
+    }
+}
+
+pub(crate) fn busy_message() -> String {
+    let path = get_path("rtimer.lock");
+    let mut pid = String::new();
+    let _ = File::open(&path).and_then(|mut f| f.read_to_string(&mut pid));
+    let holder = match pid.trim() {
+        "" => "another rtimer instance".to_string(),
+        pid => format!("another rtimer instance (pid {})", pid),
+    };
+    format!("{} is using {}", holder, path.parent().unwrap_or(&path).display())
+}
+
+// ============================================================================
+// Schema Migrations
+// ============================================================================
+
+/// Upgrades a document of one schema version to the next.
+pub(crate) type Migration = fn(&mut Value);
+
+/// A file format with a `schema_version` field and the steps that upgrade
+/// older layouts one version at a time. Version 0 is the layout from before
+/// files were versioned at all.
+pub(crate) trait Versioned: for<'de> Deserialize<'de> + Serialize + Default {
+    /// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`.
+    const MIGRATIONS: &'static [Migration];
+    const VERSION: u32 = Self::MIGRATIONS.len() as u32;
+}
+
+fn schema_version(value: &Value) -> u32 {
+    value.get("schema_version").and_then(Value::as_u64).unwrap_or(0) as u32
+}
+
+fn migrate<T: Versioned>(mut value: Value) -> serde_json::Result<T> {
+    let from = schema_version(&value) as usize;
+    for step in T::MIGRATIONS.iter().skip(from) {
+        step(&mut value);
+    }
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("schema_version".into(), T::VERSION.into());
+    }
+    serde_json::from_value(value)
+}
+
+/// v0 → v1: files gain `schema_version`; missing fields take their defaults.
+fn add_schema_version(_: &mut Value) {}
+
+impl Versioned for Config {
+    const MIGRATIONS: &'static [Migration] = &[add_schema_version];
+}
+
+/// v1 → v2: the separately incremented counters are replaced by totals
+/// computed from the session log. History used to be capped at 100 records,
+/// so whatever the old counters covered beyond it is kept as `archived`.
+/// Old records only knew their planned length; completed ones are assumed
+/// to have run it in full.
+fn derive_totals(stats: &mut Value) {
+    let Some(obj) = stats.as_object_mut() else { return };
+    let counter = |obj: &serde_json::Map<String, Value>, key: &str| {
+        obj.get(key).and_then(Value::as_u64).unwrap_or(0)
+    };
+    let total_sessions = counter(obj, "total_sessions");
+    let total_work_secs = counter(obj, "total_work_time") * 60;
+    let total_break_secs = counter(obj, "total_break_time") * 60;
+    for key in ["total_sessions", "total_work_time", "total_break_time", "sessions_today", "last_session_date", "weekly_sessions"] {
+        obj.remove(key);
+    }
+
+    let mut history = Totals::default();
+    if let Some(records) = obj.get_mut("session_history").and_then(Value::as_array_mut) {
+        for record in records {
+            let Some(r) = record.as_object_mut() else { continue };
+            let completed = r.get("completed").and_then(Value::as_bool).unwrap_or(false);
+            let planned = r.get("duration").and_then(Value::as_u64).unwrap_or(0) * 60;
+            let elapsed = if completed { planned } else { 0 };
+            r.insert("elapsed_secs".into(), elapsed.into());
+            if r.get("phase_type").and_then(Value::as_str) == Some("Work") {
+                history.work_sessions += completed as u32;
+                history.work_secs += elapsed;
+            } else {
+                history.break_secs += elapsed;
+            }
+        }
+    }
+
+    let archived = Totals {
+        work_sessions: (total_sessions as u32).saturating_sub(history.work_sessions),
+        work_secs: total_work_secs.saturating_sub(history.work_secs),
+        break_secs: total_break_secs.saturating_sub(history.break_secs),
+    };
+    obj.insert("archived".into(), serde_json::to_value(archived).unwrap_or_default());
+}
+
+/// v2 → v3: records gain `paused_secs` and an `end_reason` in place of
+/// `completed`. Incomplete records could only come from skipping.
+fn add_end_reasons(stats: &mut Value) {
+    let Some(records) = stats.get_mut("session_history").and_then(Value::as_array_mut) else { return };
+    for record in records {
+        let Some(r) = record.as_object_mut() else { continue };
+        let completed = r.remove("completed").and_then(|v| v.as_bool()).unwrap_or(true);
+        r.insert("end_reason".into(), if completed { "completed" } else { "skipped" }.into());
+        r.entry("paused_secs").or_insert(0.into());
+    }
+}
+
+impl Versioned for Statistics {
+    const MIGRATIONS: &'static [Migration] = &[add_schema_version, derive_totals, add_end_reasons];
+}
+
+impl Versioned for TimerState {
+    const MIGRATIONS: &'static [Migration] = &[add_schema_version];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EndReason, SuspendPolicy};
+    use serde_json::json;
+
+    #[test]
+    fn config_v0_without_suspend_policy() {
+        let config: Config = migrate(json!({
+            "work_duration": 50.0,
+            "rest_duration": 10.0,
+            "long_break_duration": 20.0,
+            "sessions_before_long_break": 3,
+            "sound_enabled": false,
+            "theme": "nord",
+            "auto_start_next": false,
+            "extended_break_reminder_hours": 1.5
+        })).unwrap();
+
+        assert_eq!(config.schema_version, Config::VERSION);
+        assert_eq!(config.work_duration, 50.0);
+        assert_eq!(config.sessions_before_long_break, 3);
+        assert_eq!(config.theme, "nord");
+        assert!(config.suspend_policy == SuspendPolicy::default());
+    }
+
+    #[test]
+    fn config_v0_with_suspend_policy() {
+        let config: Config = migrate(json!({
+            "work_duration": 25.0,
+            "rest_duration": 5.0,
+            "long_break_duration": 15.0,
+            "sessions_before_long_break": 4,
+            "sound_enabled": true,
+            "theme": "default",
+            "auto_start_next": true,
+            "extended_break_reminder_hours": 2.0,
+            "suspend_policy": "pause"
+        })).unwrap();
+
+        assert!(config.suspend_policy == SuspendPolicy::Pause);
+    }
+
+    #[test]
+    fn config_missing_fields_take_defaults() {
+        let config: Config = migrate(json!({ "work_duration": 40.0 })).unwrap();
+
+        assert_eq!(config.work_duration, 40.0);
+        assert_eq!(config.rest_duration, Config::default().rest_duration);
+    }
+
+    fn old_counters() -> Value {
+        json!({
+            "total_sessions": 12,
+            "total_work_time": 300,
+            "total_break_time": 60,
+            "sessions_today": 2,
+            "last_session_date": "2026-10-01",
+            "session_history": [
+                { "timestamp": "2026-10-01T09:25:00+02:00", "phase_type": "Work", "duration": 25, "completed": true },
+                { "timestamp": "2026-10-01T09:30:00+02:00", "phase_type": "Short Break", "duration": 5, "completed": true },
+                { "timestamp": "2026-10-01T09:33:00+02:00", "phase_type": "Work", "duration": 25, "completed": false }
+            ],
+            "weekly_sessions": [1, 2, 0, 0, 3, 0, 2],
+            "notes": [
+                { "timestamp": "2026-10-01T09:10:00+02:00", "content": "fix the bug", "phase": "work" }
+            ]
+        })
+    }
+
+    #[test]
+    fn stats_v0() {
+        let stats: Statistics = migrate(old_counters()).unwrap();
+
+        assert_eq!(stats.schema_version, Statistics::VERSION);
+        assert_eq!(stats.session_history.len(), 3);
+        assert_eq!(stats.notes[0].content, "fix the bug");
+        assert_eq!(stats.totals(), Totals { work_sessions: 12, work_secs: 300 * 60, break_secs: 60 * 60 });
+    }
+
+    #[test]
+    fn stats_v1_counters_become_archived() {
+        let mut value = old_counters();
+        value["schema_version"] = 1.into();
+        let stats: Statistics = migrate(value).unwrap();
+
+        assert_eq!(stats.session_history[0].elapsed_secs, 25 * 60);
+        assert_eq!(stats.session_history[2].elapsed_secs, 0);
+        assert!(stats.session_history[2].end_reason == EndReason::Skipped);
+        assert_eq!(stats.archived, Totals { work_sessions: 11, work_secs: 275 * 60, break_secs: 55 * 60 });
+    }
+
+    #[test]
+    fn stats_v1_counters_behind_history() {
+        let mut value = old_counters();
+        value["schema_version"] = 1.into();
+        value["total_sessions"] = 0.into();
+        value["total_work_time"] = 0.into();
+        let stats: Statistics = migrate(value).unwrap();
+
+        assert_eq!(stats.archived.work_sessions, 0);
+        assert_eq!(stats.totals().work_secs, 25 * 60);
+    }
+
+    #[test]
+    fn stats_v2_end_reasons() {
+        let stats: Statistics = migrate(json!({
+            "schema_version": 2,
+            "session_history": [
+                { "timestamp": "2026-10-02T10:00:00+02:00", "phase_type": "Work", "duration": 25, "elapsed_secs": 1500, "completed": true },
+                { "timestamp": "2026-10-02T10:04:00+02:00", "phase_type": "Short Break", "duration": 5, "elapsed_secs": 120, "completed": false }
+            ],
+            "archived": { "work_sessions": 3, "work_secs": 4500, "break_secs": 900 }
+        })).unwrap();
+
+        let [work, rest] = &stats.session_history[..] else { panic!("expected two records") };
+        assert!(work.end_reason == EndReason::Completed);
+        assert!(rest.end_reason == EndReason::Skipped);
+        assert!(work.started.is_none());
+        assert_eq!(rest.paused_secs, 0);
+        assert_eq!(stats.totals(), Totals { work_sessions: 4, work_secs: 6000, break_secs: 1020 });
+    }
+
+    #[test]
+    fn timer_state_v0() {
+        let state: TimerState = migrate(json!({
+            "time_remaining_secs": 600,
+            "phase": "short_break",
+            "session_count": 3,
+            "paused": true
+        })).unwrap();
+
+        assert_eq!(state.schema_version, TimerState::VERSION);
+        assert_eq!(state.time_remaining_secs, 600);
+        assert_eq!(state.phase, "short_break");
+        assert!(state.paused);
+    }
+
+    #[test]
+    fn current_version_round_trips() {
+        let value = serde_json::to_value(Statistics::default()).unwrap();
+        assert_eq!(schema_version(&value), Statistics::VERSION);
+        let stats: Statistics = migrate(value).unwrap();
+        assert_eq!(stats.schema_version, Statistics::VERSION);
+    }
+}
@@ -0,0 +1,106 @@
+//! Fixtures shared by the unit tests.
+
+use crate::{SessionRecord, Statistics};
+use serde_json::{json, Value};
+use std::{
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// A path in the temp directory, unique to the test, that is removed along
+/// with SQLite's side files when dropped.
+pub(crate) struct TempPath(PathBuf);
+
+impl TempPath {
+    pub(crate) fn new(name: &str) -> Self {
+        let path = Self(std::env::temp_dir().join(format!("rtimer-test-{}-{}", std::process::id(), name)));
+        path.remove();
+        path
+    }
+
+    /// A temp file holding `text`.
+    pub(crate) fn with(name: &str, text: &str) -> Self {
+        let path = Self::new(name);
+        fs::write(&path.0, text).unwrap();
+        path
+    }
+
+    fn remove(&self) {
+        let _ = fs::remove_dir_all(&self.0);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = fs::remove_file(format!("{}{}", self.0.display(), suffix));
+        }
+    }
+}
+
+impl Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+/// A completed 25-minute session as stored in stats.json.
+pub(crate) fn session(started: &str, ended: &str, phase: &str) -> Value {
+    json!({
+        "started": started, "timestamp": ended, "phase_type": phase, "duration": 25,
+        "elapsed_secs": 1500, "paused_secs": 0, "end_reason": "completed",
+    })
+}
+
+pub(crate) fn record(session: Value) -> SessionRecord {
+    serde_json::from_value(session).unwrap()
+}
+
+pub(crate) fn stats(value: Value) -> Statistics {
+    serde_json::from_value(value).unwrap()
+}
+
+/// Statistics with something in every field: a labelled work session on
+/// task 2 with two interruptions, a skipped break, a note and two tasks.
+pub(crate) fn sample() -> Statistics {
+    let mut work = session("2026-10-01T09:00:00+02:00", "2026-10-01T09:27:00+02:00", "Work");
+    work["paused_secs"] = json!(120);
+    work["task"] = json!(2);
+    work["project"] = json!("thesis");
+    work["tags"] = json!(["writing", "deep"]);
+    work["interruptions"] = json!([
+        { "timestamp": "2026-10-01T09:10:00+02:00", "duration_secs": 60, "kind": "external", "reason": "phone" },
+        { "timestamp": "2026-10-01T09:20:00+02:00", "duration_secs": 60, "kind": null },
+    ]);
+    let mut skipped = session("2026-10-01T09:27:00+02:00", "2026-10-01T09:28:00+02:00", "Short Break");
+    skipped["end_reason"] = json!("skipped");
+    skipped["elapsed_secs"] = json!(60);
+
+    stats(json!({
+        "session_history": [work, skipped],
+        "notes": [{
+            "timestamp": "2026-10-01T09:15:00+02:00", "content": "outline \"intro\", #chapter2",
+            "phase": "work", "project": "thesis", "tags": ["chapter2"],
+        }],
+        "tasks": [
+            { "id": 1, "name": "Read papers", "estimate": 3, "done": true, "created": "2026-09-30T10:00:00+02:00" },
+            { "id": 2, "name": "Write draft", "estimate": 5, "done": false, "created": "2026-09-30T10:05:00+02:00" },
+        ],
+        "next_task_id": 4,
+        "active_task": 2,
+        "active_project": "thesis",
+        "active_tags": ["writing"],
+        "streak_warned": "2026-10-01",
+        "archived": { "work_sessions": 4, "work_secs": 6000, "break_secs": 900 },
+    }))
+}
@@ -0,0 +1,243 @@
+use crate::storage::get_path;
+use crate::{SessionRecord, Statistics};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// Timewarrior's own timestamp format, used in its data files.
+const DATA_FMT: &str = "%Y%m%dT%H%M%SZ";
+/// How times are passed to `timew` on the command line.
+const CLI_FMT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// How focus sessions are mirrored into Timewarrior.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Mode {
+    #[default]
+    Off,
+    /// Append each finished session to Timewarrior's data files
+    Data,
+    /// Run `timew start` when focus begins and `timew stop` when it ends
+    Command,
+}
+
+/// Mirrors work phases into Timewarrior as they start and end.
+pub(crate) struct Mirror {
+    pub(crate) mode: Mode,
+    /// Log what would be sent to `timewarrior.log` in the data directory
+    /// instead of touching Timewarrior.
+    pub(crate) dry_run: bool,
+    /// Whether `timew start` was run for the current work phase.
+    tracking: bool,
+}
+
+impl Mirror {
+    pub(crate) fn new(mode: Mode, dry_run: bool) -> Self {
+        Self { mode, dry_run, tracking: false }
+    }
+
+    /// A work phase began running at `at`.
+    pub(crate) fn start(&mut self, at: DateTime<Utc>, tags: &[String]) -> io::Result<()> {
+        if self.mode != Mode::Command {
+            return Ok(());
+        }
+        let mut args = vec!["start".to_string(), at.format(CLI_FMT).to_string()];
+        args.extend(tags.iter().cloned());
+        self.timew(&args)?;
+        self.tracking = true;
+        Ok(())
+    }
+
+    /// A work phase ended and was logged as `session`.
+    pub(crate) fn end(&mut self, session: &SessionRecord, tags: &[String]) -> io::Result<()> {
+        let interval = interval(session).filter(|_| session.elapsed_secs > 0);
+        match (self.mode, interval) {
+            (Mode::Off, _) | (Mode::Data, None) => Ok(()),
+            (Mode::Data, Some((start, end))) => self.append(start, end, tags),
+            (Mode::Command, None) if self.tracking => {
+                self.tracking = false;
+                self.timew(&["cancel".to_string()])
+            }
+            (Mode::Command, Some((_, end))) if self.tracking => self.stop_at(end),
+            // A phase started paused, or before rtimer last restarted, was
+            // never started in Timewarrior; record it after the fact and let
+            // Timewarrior trim whatever overlaps.
+            (Mode::Command, Some((start, end))) => {
+                let mut args = vec![
+                    "track".to_string(),
+                    start.format(CLI_FMT).to_string(),
+                    "-".to_string(),
+                    end.format(CLI_FMT).to_string(),
+                ];
+                args.extend(tags.iter().cloned());
+                args.push(":adjust".into());
+                self.timew(&args)
+            }
+            (Mode::Command, None) => Ok(()),
+        }
+    }
+
+    /// Stops tracking when rtimer exits in the middle of a work phase.
+    pub(crate) fn stop_at(&mut self, at: DateTime<Utc>) -> io::Result<()> {
+        if !self.tracking {
+            return Ok(());
+        }
+        self.tracking = false;
+        self.timew(&["stop".to_string(), at.format(CLI_FMT).to_string()])
+    }
+
+    fn timew(&self, args: &[String]) -> io::Result<()> {
+        if self.dry_run {
+            return dry_run_log(&format!("timew {}", args.join(" ")));
+        }
+        let output = Command::new("timew").args(args).stdin(Stdio::null()).output()?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(io::Error::other(format!("timew {} failed: {}", args[0], stderr.trim())))
+        }
+    }
+
+    /// Appends an `inc` line to the month's data file, the way `timew track`
+    /// would write it.
+    fn append(&self, start: DateTime<Utc>, end: DateTime<Utc>, tags: &[String]) -> io::Result<()> {
+        let dir = database_dir().ok_or_else(|| io::Error::other("no Timewarrior database found; set TIMEWARRIORDB"))?;
+        let path = dir.join("data").join(start.format("%Y-%m.data").to_string());
+        let mut line = format!("inc {} - {}", start.format(DATA_FMT), end.format(DATA_FMT));
+        if !tags.is_empty() {
+            let tags: Vec<String> = tags.iter().map(|t| quote(t)).collect();
+            line = format!("{} # {}", line, tags.join(" "));
+        }
+        if self.dry_run {
+            return dry_run_log(&format!("append to {}: {}", path.display(), line));
+        }
+        fs::create_dir_all(path.parent().unwrap_or(&dir))?;
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", line)
+    }
+}
+
+/// Timewarrior's database directory: `$TIMEWARRIORDB`, `~/.timewarrior`, or
+/// `$XDG_DATA_HOME/timewarrior` for newer versions.
+pub(crate) fn database_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("TIMEWARRIORDB") {
+        return Some(PathBuf::from(dir));
+    }
+    let home = std::env::var_os("HOME").map(PathBuf::from)?;
+    let data = std::env::var_os("XDG_DATA_HOME").map_or_else(|| home.join(".local/share"), PathBuf::from);
+    [home.join(".timewarrior"), data.join("timewarrior")].into_iter().find(|d| d.join("data").is_dir())
+}
+
+/// The Timewarrior tags for a work phase: its task, project and #tags.
+pub(crate) fn tags(stats: &Statistics, task: Option<u64>, project: Option<&str>, tags: &[String]) -> Vec<String> {
+    let task = task.and_then(|id| stats.task(id)).map(|t| t.name.clone());
+    task.into_iter().chain(project.map(String::from)).chain(tags.iter().cloned()).collect()
+}
+
+fn interval(session: &SessionRecord) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let end = DateTime::parse_from_rfc3339(&session.timestamp).ok()?.with_timezone(&Utc);
+    let start = DateTime::parse_from_rfc3339(session.started.as_deref()?).ok()?.with_timezone(&Utc);
+    (start < end).then_some((start, end))
+}
+
+/// Quotes a tag for a data file if it wouldn't read back as one word.
+/// Within quotes, backslashes and quotes are escaped with a backslash.
+fn quote(tag: &str) -> String {
+    if tag.contains(|c: char| c.is_whitespace() || c == '"' || c == '#') {
+        format!("\"{}\"", tag.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        tag.to_string()
+    }
+}
+
+fn dry_run_log(action: &str) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(get_path("timewarrior.log"))?;
+    writeln!(file, "{} {}", chrono::Local::now().to_rfc3339(), action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{self, TempPath};
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    /// Runs `f` and returns the actions it logged. Dry runs all write to
+    /// the same log, so tests take turns and clear it afterwards.
+    fn logged(f: impl FnOnce() -> io::Result<()>) -> Vec<String> {
+        static LOG: Mutex<()> = Mutex::new(());
+        let _turn = LOG.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = TempPath::new("timewarrior");
+        crate::storage::set_data_dir(dir.to_path_buf());
+        f().unwrap();
+        let log = fs::read_to_string(get_path("timewarrior.log")).unwrap_or_default();
+        log.lines().map(|l| l.split_once(' ').unwrap().1.to_string()).collect()
+    }
+
+    fn mirror(mode: Mode, tracking: bool) -> Mirror {
+        Mirror { mode, dry_run: true, tracking }
+    }
+
+    fn session(elapsed_secs: u64) -> SessionRecord {
+        let mut session = testing::session("2026-10-01T09:00:00+02:00", "2026-10-01T09:25:00+02:00", "Work");
+        session["elapsed_secs"] = json!(elapsed_secs);
+        testing::record(session)
+    }
+
+    #[test]
+    fn tracked_phase_is_stopped() {
+        let mut m = mirror(Mode::Command, true);
+        assert_eq!(logged(|| m.end(&session(1500), &[])), ["timew stop 2026-10-01T07:25:00Z"]);
+        assert!(!m.tracking);
+    }
+
+    #[test]
+    fn tracked_phase_without_focus_is_cancelled() {
+        let mut m = mirror(Mode::Command, true);
+        assert_eq!(logged(|| m.end(&session(0), &[])), ["timew cancel"]);
+        assert!(!m.tracking);
+    }
+
+    #[test]
+    fn untracked_phase_is_tracked_afterwards() {
+        let mut m = mirror(Mode::Command, false);
+        let tags = ["thesis".to_string()];
+        assert_eq!(
+            logged(|| m.end(&session(1500), &tags)),
+            ["timew track 2026-10-01T07:00:00Z - 2026-10-01T07:25:00Z thesis :adjust"],
+        );
+        assert!(logged(|| m.end(&session(0), &tags)).is_empty());
+    }
+
+    #[test]
+    fn nothing_is_sent_when_off() {
+        let mut m = mirror(Mode::Off, true);
+        assert!(logged(|| m.end(&session(1500), &[])).is_empty());
+        assert!(logged(|| mirror(Mode::Data, false).end(&session(0), &[])).is_empty());
+    }
+
+    #[test]
+    fn quitting_stops_only_what_was_started() {
+        let mut m = mirror(Mode::Command, false);
+        let at = DateTime::parse_from_rfc3339("2026-10-01T09:10:00+02:00").unwrap().with_timezone(&Utc);
+        assert!(logged(|| m.stop_at(at)).is_empty());
+        assert_eq!(logged(|| m.start(at, &[])), ["timew start 2026-10-01T07:10:00Z"]);
+        assert_eq!(logged(|| m.stop_at(at)), ["timew stop 2026-10-01T07:10:00Z"]);
+    }
+
+    #[test]
+    fn quoted_tags_read_back() {
+        let tags = ["plain", "c#", "say \"hi\"", r"back\slash", r"quoted \ end\", r#"a\"b"#];
+        let line = tags.map(quote).join(" ");
+        assert_eq!(
+            crate::import::timewarrior_tags(&line),
+            ["plain", "c#", "say-\"hi\"", r"back\slash", r"quoted-\-end\", r#"a\"b"#],
+        );
+    }
+}